@@ -0,0 +1,46 @@
+#![no_main]
+
+use std::collections::HashMap;
+
+use grok_rs::{CompileOptions, Grok, MissingPolicy, Value};
+use libfuzzer_sys::fuzz_target;
+
+// the data is split into lines: the options, the definition of `A`, the expression, and the input.
+fuzz_target!(|data: &[u8]| {
+    let Ok(data) = std::str::from_utf8(data) else {
+        return;
+    };
+    let mut lines = data.splitn(4, '\n');
+    let flags = lines.next().unwrap_or_default().as_bytes();
+    let definition = lines.next().unwrap_or_default();
+    let expr = lines.next().unwrap_or_default();
+    let input = lines.next().unwrap_or_default();
+
+    let flag = |i: usize| flags.get(i).is_some_and(|b| b % 2 == 1);
+    let opts = CompileOptions::default()
+        .named_capture_only(flag(0))
+        .case_insensitive(flag(1))
+        .exact(flag(2))
+        .lazy_greedydata(flag(3))
+        .collect_arrays(flag(4));
+
+    let mut grok = Grok::default();
+    grok.add_pattern("A", definition);
+    let _ = grok.complexity(expr, opts.named_capture_only);
+    if let Ok(pattern) = grok.compile_with_opts(expr, &opts) {
+        let _ = pattern.parse(input);
+        let _ = pattern.validate(input);
+        let _ = pattern.schema();
+        let _ = pattern.match_failure(input);
+
+        let fields = pattern.schema();
+        let policy = fields
+            .iter()
+            .map(|field| (field.name.as_str(), MissingPolicy::Default(Value::Null)))
+            .collect::<HashMap<_, _>>();
+        let _ = pattern.parse_with_policy(input, &policy);
+    }
+    if let Ok(pattern) = grok.compile_bytes(expr, opts.named_capture_only) {
+        let _ = pattern.parse(input.as_bytes());
+    }
+});
@@ -0,0 +1,87 @@
+//! Derive macro for the `grok_rs::FromGrok` trait, enable it by the `derive` feature of `grok-rs`.
+//!
+//! Every field is populated from the parsed map by its name, and converted by its type
+//! through `grok_rs::FromValue`. The alias can be specified by `#[grok(rename = "...")]`
+//! if it is not a valid Rust identifier, e.g. `destination.ip`.
+//!
+//! ```ignore
+//! use std::net::IpAddr;
+//! use grok_rs::{FromGrok, Grok};
+//!
+//! #[derive(FromGrok)]
+//! struct Destination {
+//!     #[grok(rename = "destination.ip")]
+//!     ip: IpAddr,
+//!     #[grok(rename = "destination.port")]
+//!     port: u16,
+//! }
+//! ```
+use proc_macro::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, LitStr};
+
+#[proc_macro_derive(FromGrok, attributes(grok))]
+pub fn derive_from_grok(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "FromGrok can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "FromGrok can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut inits = Vec::with_capacity(fields.len());
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let mut name = ident.to_string();
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("grok")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported grok attribute, expected `rename`"))
+                }
+            })?;
+        }
+
+        // spanned by the type, so the type without `FromValue` is reported at the field
+        let ty = &field.ty;
+        inits.push(quote_spanned! {ty.span()=>
+            #ident: <#ty as ::grok_rs::FromValue>::from_field(map, #name)?
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::grok_rs::FromGrok for #ident #ty_generics #where_clause {
+            fn from_grok(
+                map: &::std::collections::HashMap<::std::string::String, ::grok_rs::Value>,
+            ) -> ::std::result::Result<Self, ::grok_rs::GrokError> {
+                ::std::result::Result::Ok(Self {
+                    #(#inits,)*
+                })
+            }
+        }
+    })
+}
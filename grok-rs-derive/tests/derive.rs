@@ -0,0 +1,62 @@
+use std::net::IpAddr;
+
+use grok_rs::{FromGrok, Grok, GrokError};
+
+#[derive(Debug, PartialEq, FromGrok)]
+struct Destination {
+    #[grok(rename = "destination.ip")]
+    ip: IpAddr,
+    #[grok(rename = "destination.port")]
+    port: u16,
+    #[grok(rename = "destination.domain")]
+    domain: Option<String>,
+}
+
+fn grok() -> Grok {
+    Grok::from([
+        (
+            "NGINX_HOST",
+            r"(?:%{IP:destination.ip}|%{NGINX_NOTSEPARATOR:destination.domain})(:%{NUMBER:destination.port})?",
+        ),
+        ("NGINX_NOTSEPARATOR", r"[^\t ,:]+"),
+        ("IP", r"\b(?:\d{1,3}\.){3}\d{1,3}\b"),
+        ("NUMBER", r"\d+"),
+    ])
+}
+
+#[test]
+fn test_derive() {
+    let pattern = grok().compile("%{NGINX_HOST}", true).unwrap();
+
+    let expected = Destination {
+        ip: "127.0.0.1".parse().unwrap(),
+        port: 1234,
+        domain: None,
+    };
+    assert_eq!(
+        Some(expected),
+        pattern.parse_into::<Destination>("127.0.0.1:1234").unwrap()
+    );
+
+    let map = pattern.parse("127.0.0.1:1234").unwrap();
+    assert_eq!(1234, Destination::from_grok(&map).unwrap().port);
+
+    assert_eq!(None, pattern.parse_into::<Destination>("").unwrap());
+}
+
+#[test]
+fn test_derive_error() {
+    let pattern = grok().compile("%{NGINX_HOST}", true).unwrap();
+
+    // the domain branch is taken, so the ip is missing
+    assert_eq!(
+        Err(GrokError::MissingField("destination.ip".to_string())),
+        pattern.parse_into::<Destination>("example.com:1234")
+    );
+
+    // the port is out of range of u16
+    assert!(matches!(
+        pattern.parse_into::<Destination>("127.0.0.1:65536"),
+        Err(GrokError::Conversion { .. })
+    ));
+}
@@ -0,0 +1,9 @@
+use grok_rs::FromGrok;
+
+#[derive(FromGrok)]
+enum Method {
+    Get,
+    Post,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use grok_rs::FromGrok;
+
+#[derive(FromGrok)]
+struct Port(u16);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use grok_rs::FromGrok;
+
+#[derive(FromGrok)]
+struct Destination {
+    #[grok(alias = "destination.port")]
+    port: u16,
+}
+
+fn main() {}
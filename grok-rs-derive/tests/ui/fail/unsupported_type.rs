@@ -0,0 +1,10 @@
+use std::time::Duration;
+
+use grok_rs::FromGrok;
+
+#[derive(FromGrok)]
+struct Request {
+    took: Duration,
+}
+
+fn main() {}
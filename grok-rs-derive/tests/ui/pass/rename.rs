@@ -0,0 +1,13 @@
+use std::net::IpAddr;
+
+use grok_rs::FromGrok;
+
+#[derive(FromGrok)]
+struct Destination {
+    #[grok(rename = "destination.ip")]
+    ip: IpAddr,
+    port: Option<u16>,
+    tags: Vec<String>,
+}
+
+fn main() {}
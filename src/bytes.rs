@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use regex::bytes::Regex;
+
+use crate::{
+    check_duplicate_groups, convert, map::markers, AliasType, CompileOptions, Grok, GrokError,
+    Value,
+};
+
+/// The pattern compiled against bytes, see [`Grok::compile_bytes`].
+#[derive(Debug)]
+pub struct BytesPattern {
+    regex: Regex,
+    alias: HashMap<String, AliasType>,
+}
+
+impl BytesPattern {
+    /// parse the input bytes based on the pattern, and rename the captured group based on alias.
+    ///  - the value without type is kept as [`Value::Bytes`].
+    ///  - the value with type must be valid UTF-8 to be converted, otherwise an error will be returned.
+    ///  - if the value can't be captured, then an empty map will be returned.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::{Grok, Value};
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile_bytes("%{IPV4:ip}", true).unwrap();
+    /// let result = pattern.parse(b"\xff\xfe 10.0.0.1").unwrap();
+    /// assert_eq!(Some(&Value::Bytes(b"10.0.0.1".to_vec())), result.get("ip"));
+    /// ```
+    pub fn parse(&self, s: &[u8]) -> Result<HashMap<String, Value>, GrokError> {
+        let mut map = HashMap::new();
+        let Some(caps) = self.regex.captures(s) else {
+            return Ok(map);
+        };
+
+        for name in self.regex.capture_names().flatten() {
+            if let Some(m) = caps.name(name) {
+                let (key, value) = match self.alias.get(name) {
+                    Some((alias, Some(type_))) => (alias.as_str(), typed(m.as_bytes(), type_)?),
+                    Some((alias, None)) => (alias.as_str(), Value::Bytes(m.as_bytes().to_vec())),
+                    None => (name, Value::Bytes(m.as_bytes().to_vec())),
+                };
+                map.insert(key.to_string(), value);
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+fn typed(value: &[u8], type_: &str) -> Result<Value, GrokError> {
+    match std::str::from_utf8(value) {
+        Ok(value) => convert(value, Some(type_)),
+        Err(e) => Err(GrokError::Conversion {
+            value: String::from_utf8_lossy(value).into_owned(),
+            reason: e.to_string(),
+        }),
+    }
+}
+
+impl Grok {
+    /// Compile the pattern against bytes, e.g. to search the binary or mixed-encoding logs
+    /// where UTF-8 can't be assumed.
+    ///
+    /// The Unicode mode is disabled, so `.` and the negated classes match any byte,
+    /// and `\w`, `\d`, `\s` only match ASCII.
+    /// The `@key` and `@value` pairs of [`Value::Map`] are not supported, `UnsupportedMap` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile_bytes("%{WORD:method} %{GREEDYDATA:body}", true).unwrap();
+    /// assert_eq!(2, pattern.parse(b"POST \x00\x01\x02").unwrap().len());
+    /// ```
+    pub fn compile_bytes(
+        &self,
+        s: &str,
+        named_capture_only: bool,
+    ) -> Result<BytesPattern, GrokError> {
+        let opts = CompileOptions::default()
+            .named_capture_only(named_capture_only)
+            .unicode(false);
+        let (regex, alias) = self.expand(s, &opts)?;
+        check_duplicate_groups(&regex, &alias)?;
+        if let Some((field, _, _)) = markers(&alias).first() {
+            return Err(GrokError::UnsupportedMap {
+                field: field.to_string(),
+            });
+        }
+        let regex = Regex::new(&regex).map_err(|e| GrokError::Regex(e.to_string()))?;
+        Ok(BytesPattern { regex, alias })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_bytes() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile_bytes("%{IPV4:ip} %{INT:port:int} %{GREEDYDATA:tail}", true)
+            .unwrap();
+
+        let input = b"10.0.0.1 8080 \xc3\x28\xff\xfe";
+        assert_eq!(
+            HashMap::from([
+                ("ip".to_string(), Value::Bytes(b"10.0.0.1".to_vec())),
+                ("port".to_string(), Value::Int(8080)),
+                (
+                    "tail".to_string(),
+                    Value::Bytes(b"\xc3\x28\xff\xfe".to_vec())
+                ),
+            ]),
+            pattern.parse(input).unwrap()
+        );
+        assert!(pattern.parse(b"\xff\xfe").unwrap().is_empty());
+
+        assert_eq!(
+            GrokError::UnsupportedMap {
+                field: "kv".to_string()
+            },
+            grok.compile_bytes(r"(?:%{WORD:@key}=%{DATA:@value} ?)+", true)
+                .unwrap_err()
+        );
+
+        let pattern = grok.compile_bytes("%{DATA:n:int}$", true).unwrap();
+        assert!(matches!(
+            pattern.parse(b"1\xff"),
+            Err(GrokError::Conversion { .. })
+        ));
+    }
+}
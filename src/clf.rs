@@ -0,0 +1,119 @@
+use std::{collections::HashMap, net::IpAddr};
+
+use crate::{CompileOptions, FromGrok, FromValue, Grok, GrokError, Pattern, Value};
+
+/// the Common Log Format, optionally followed by the referrer and the user agent of the Combined Log Format.
+const CLF: &str = r#"^%{HTTPD_COMMONLOG}(?: "(?:-|%{DATA:http.request.referrer})" "(?:-|%{DATA:user_agent.original})")?$"#;
+
+lazy_static::lazy_static! {
+    static ref CLF_PATTERN: Pattern = Grok::default()
+        .compile_with_opts(CLF, &CompileOptions::default().named_capture_only(true))
+        .unwrap();
+}
+
+/// A line of the Common Log Format or the Combined Log Format, see [`parse_clf`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClfRecord {
+    pub client: IpAddr,
+    /// the RFC 1413 identity of the client, `None` if it's `-`.
+    pub ident: Option<String>,
+    /// the authenticated user, `None` if it's `-`.
+    pub user: Option<String>,
+    /// the time as it's logged, e.g. `10/Oct/2000:13:55:36 -0700`.
+    pub timestamp: String,
+    /// the method, the url and the version are `None` if the request line is malformed, e.g. `"-"`.
+    pub method: Option<String>,
+    pub url: Option<String>,
+    pub http_version: Option<String>,
+    pub status: u16,
+    /// the size of the response body, `-` is 0.
+    pub bytes: u64,
+    /// only logged by the Combined Log Format.
+    pub referrer: Option<String>,
+    /// only logged by the Combined Log Format.
+    pub user_agent: Option<String>,
+}
+
+impl FromGrok for ClfRecord {
+    fn from_grok(map: &HashMap<String, Value>) -> Result<Self, GrokError> {
+        Ok(Self {
+            client: FromValue::from_field(map, "source.address")?,
+            ident: FromValue::from_field(map, "apache.access.user.identity")?,
+            user: FromValue::from_field(map, "user.name")?,
+            timestamp: FromValue::from_field(map, "timestamp")?,
+            method: FromValue::from_field(map, "http.request.method")?,
+            url: FromValue::from_field(map, "url.original")?,
+            http_version: FromValue::from_field(map, "http.version")?,
+            status: FromValue::from_field(map, "http.response.status_code")?,
+            bytes: Option::<u64>::from_field(map, "http.response.body.bytes")?.unwrap_or(0),
+            referrer: FromValue::from_field(map, "http.request.referrer")?,
+            user_agent: FromValue::from_field(map, "user_agent.original")?,
+        })
+    }
+}
+
+/// parse a line of the Common Log Format or the Combined Log Format with the `HTTPD_COMMONLOG` pattern.
+///  - [`GrokError::NoMatch`] is returned if the whole line can't be matched.
+///  - the client must be an IP address, the hostname is a [`GrokError::Conversion`] error.
+///
+/// # Example
+/// ```
+/// use grok_rs::parse_clf;
+///
+/// let line = r#"127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /apache_pb.gif HTTP/1.0" 200 2326"#;
+/// let record = parse_clf(line).unwrap();
+/// assert_eq!(200, record.status);
+/// assert_eq!(Some("frank".to_string()), record.user);
+/// ```
+pub fn parse_clf(line: &str) -> Result<ClfRecord, GrokError> {
+    CLF_PATTERN.parse_into(line)?.ok_or(GrokError::NoMatch)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_combined() {
+        let line = r#"83.149.9.216 - - [17/May/2015:10:05:03 +0000] "GET /presentations/logstash-monitorama-2013/images/kibana-search.png HTTP/1.1" 200 203023 "http://semicomplete.com/presentations/logstash-monitorama-2013/" "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_9_1) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/32.0.1700.77 Safari/537.36""#;
+        assert_eq!(
+            ClfRecord {
+                client: IpAddr::V4(Ipv4Addr::new(83, 149, 9, 216)),
+                ident: None,
+                user: None,
+                timestamp: "17/May/2015:10:05:03 +0000".to_string(),
+                method: Some("GET".to_string()),
+                url: Some(
+                    "/presentations/logstash-monitorama-2013/images/kibana-search.png".to_string()
+                ),
+                http_version: Some("1.1".to_string()),
+                status: 200,
+                bytes: 203023,
+                referrer: Some(
+                    "http://semicomplete.com/presentations/logstash-monitorama-2013/".to_string()
+                ),
+                user_agent: Some("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_9_1) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/32.0.1700.77 Safari/537.36".to_string()),
+            },
+            parse_clf(line).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_common() {
+        let line = r#"::1 - - [10/Oct/2000:13:55:36 -0700] "-" 408 -"#;
+        let record = parse_clf(line).unwrap();
+        assert_eq!("::1".parse::<IpAddr>().unwrap(), record.client);
+        assert_eq!(None, record.method);
+        assert_eq!(408, record.status);
+        assert_eq!(0, record.bytes);
+        assert_eq!(None, record.user_agent);
+
+        assert_eq!(Err(GrokError::NoMatch), parse_clf("not a log line"));
+        assert!(matches!(
+            parse_clf(r#"example.com - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.0" 200 1"#),
+            Err(GrokError::Conversion { .. })
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{Grok, GrokError};
+
+/// The structured pattern definitions, e.g. in TOML:
+///
+/// ```toml
+/// [patterns]
+/// NAME = "[a-z]+"
+/// PORT = { regex = "\\d+", type = "int" }
+/// ```
+#[derive(Debug, Deserialize)]
+struct Config {
+    #[serde(default)]
+    patterns: HashMap<String, Definition>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Definition {
+    Regex(String),
+    Typed {
+        regex: String,
+        #[serde(rename = "type")]
+        type_: Option<String>,
+    },
+}
+
+impl Grok {
+    fn from_config(config: Config) -> Self {
+        let mut grok = Grok::default();
+        for (name, definition) in config.patterns {
+            match definition {
+                Definition::Regex(regex) => grok.add_pattern(name, regex),
+                Definition::Typed { regex, type_ } => {
+                    if let Some(type_) = type_ {
+                        grok.pattern_types.insert(name.clone(), type_);
+                    }
+                    grok.add_pattern(name, regex);
+                }
+            }
+        }
+        grok
+    }
+
+    /// Create a Grok with the patterns defined in a TOML document, the default patterns are still available.
+    ///
+    /// Each pattern under the `patterns` table is either a regex, or a table of the `regex` and the default `type`.
+    /// The default type is applied when the placeholder of the pattern doesn't specify a type.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::{Grok, Value};
+    ///
+    /// let grok = Grok::from_toml_str(r#"
+    /// [patterns]
+    /// NAME = "[a-z]+"
+    /// PORT = { regex = "\\d+", type = "int" }
+    /// "#).unwrap();
+    /// let pattern = grok.compile("%{NAME:name}:%{PORT:port}", true).unwrap();
+    /// assert_eq!(Some(&Value::Int(80)), pattern.parse("host:80").unwrap().get("port"));
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(s: &str) -> Result<Self, GrokError> {
+        let config = toml::from_str(s).map_err(|e| GrokError::InvalidConfig(e.to_string()))?;
+        Ok(Self::from_config(config))
+    }
+
+    /// Create a Grok with the patterns defined in a YAML document, see [`Grok::from_toml_str`] for the structure.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::{Grok, Value};
+    ///
+    /// let grok = Grok::from_yaml_str(r#"
+    /// patterns:
+    ///   NAME: "[a-z]+"
+    ///   PORT:
+    ///     regex: \d+
+    ///     type: int
+    /// "#).unwrap();
+    /// let pattern = grok.compile("%{NAME:name}:%{PORT:port}", true).unwrap();
+    /// assert_eq!(Some(&Value::Int(80)), pattern.parse("host:80").unwrap().get("port"));
+    /// ```
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_str(s: &str) -> Result<Self, GrokError> {
+        let config =
+            serde_yaml::from_str(s).map_err(|e| GrokError::InvalidConfig(e.to_string()))?;
+        Ok(Self::from_config(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    use super::*;
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_from_toml_str() {
+        let grok = Grok::from_toml_str(
+            r#"
+            [patterns]
+            METHOD = "GET|POST"
+            STATUS = { regex = "\\d{3}", type = "int" }
+            "#,
+        )
+        .unwrap();
+
+        let pattern = grok
+            .compile("%{METHOD:method} %{STATUS:status}", true)
+            .unwrap();
+        let expected = HashMap::from([
+            ("method".to_string(), Value::String("GET".into())),
+            ("status".to_string(), Value::Int(200)),
+        ]);
+        assert_eq!(expected, pattern.parse("GET 200").unwrap());
+
+        // the explicit type overrides the default one
+        let pattern = grok.compile("%{STATUS:status:float}", true).unwrap();
+        assert_eq!(
+            Some(&Value::Float(200.0)),
+            pattern.parse("200").unwrap().get("status")
+        );
+        // the default patterns are still available
+        assert!(grok.compile("%{IP}", true).is_ok());
+
+        assert!(matches!(
+            Grok::from_toml_str("[patterns]\nNAME = 1"),
+            Err(GrokError::InvalidConfig(_))
+        ));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_from_yaml_str() {
+        let grok = Grok::from_yaml_str(
+            r#"
+            patterns:
+              METHOD: GET|POST
+              STATUS:
+                regex: \d{3}
+                type: int
+            "#,
+        )
+        .unwrap();
+
+        let pattern = grok
+            .compile("%{METHOD:method} %{STATUS:status}", true)
+            .unwrap();
+        assert_eq!(
+            Some(&Value::Int(201)),
+            pattern.parse("POST 201").unwrap().get("status")
+        );
+
+        assert!(matches!(
+            Grok::from_yaml_str("patterns: [1, 2]"),
+            Err(GrokError::InvalidConfig(_))
+        ));
+    }
+}
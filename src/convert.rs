@@ -0,0 +1,142 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::{GrokError, Value};
+
+/// the types which can be specified by `%{PATTERN:alias:type}`.
+const TYPES: [&str; 9] = [
+    "int", "long", "float", "double", "bool", "boolean", "monthnum", "daynum", "ip",
+];
+
+pub(crate) fn is_builtin_type(type_: &str) -> bool {
+    #[cfg(feature = "chrono")]
+    if crate::date::is_date_type(type_) {
+        return true;
+    }
+    TYPES.contains(&type_) || hex_len(type_).is_some()
+}
+
+/// convert the value to the specified type, if no type is specified, then the value will be kept as string.
+pub(crate) fn convert(value: &str, type_: Option<&str>) -> Result<Value, GrokError> {
+    let conversion_error = |reason: String| GrokError::Conversion {
+        value: value.to_string(),
+        reason,
+    };
+
+    match type_ {
+        Some("int" | "long") => value
+            .parse::<i64>()
+            .map(Value::Int)
+            .map_err(|e| conversion_error(e.to_string())),
+        Some("float" | "double") => value
+            .parse::<f64>()
+            .map(Value::Float)
+            .map_err(|e| conversion_error(e.to_string())),
+        Some("bool" | "boolean") => value
+            .parse::<bool>()
+            .map(Value::Bool)
+            .map_err(|e| conversion_error(e.to_string())),
+        Some("monthnum") => month_number(value)
+            .map(Value::Int)
+            .ok_or_else(|| conversion_error("invalid month".to_string())),
+        Some("daynum") => day_number(value)
+            .map(Value::Int)
+            .ok_or_else(|| conversion_error("invalid day".to_string())),
+        Some("ip") => canonical_ip(value)
+            .map(Value::String)
+            .ok_or_else(|| conversion_error("invalid IP address".to_string())),
+        #[cfg(feature = "chrono")]
+        Some(type_) if crate::date::is_date_type(type_) => crate::date::parse_date(value, type_)
+            .map(Value::DateTime)
+            .map_err(|reason| conversion_error(reason.to_string())),
+        Some(type_) if hex_len(type_).is_some() => hex(value, type_)
+            .map(Value::String)
+            .map_err(conversion_error),
+        _ => Ok(Value::String(value.to_string())),
+    }
+}
+
+/// the expected length of the `hex` type, e.g. 64 of `hex(64)`, or `Some(None)` of `hex` for any length.
+fn hex_len(type_: &str) -> Option<Option<usize>> {
+    if type_ == "hex" {
+        return Some(None);
+    }
+    let len = type_.strip_prefix("hex(")?.strip_suffix(')')?;
+    len.parse::<usize>().ok().filter(|len| *len > 0).map(Some)
+}
+
+/// the lowercased hex digits, e.g. of the hashes, with the expected length of the type.
+fn hex(value: &str, type_: &str) -> Result<String, String> {
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("invalid hex digits".to_string());
+    }
+    match hex_len(type_) {
+        Some(Some(len)) if value.len() != len => Err(format!("expected {len} hex digits")),
+        _ => Ok(value.to_ascii_lowercase()),
+    }
+}
+
+/// the IPv6 address in the compressed and lowercase form, e.g. `2001:db8::1`, the zone after `%` is kept.
+/// the IPv4 address is kept as it is.
+fn canonical_ip(value: &str) -> Option<String> {
+    if value.parse::<Ipv4Addr>().is_ok() {
+        return Some(value.to_string());
+    }
+
+    let (addr, zone) = match value.split_once('%') {
+        Some((addr, zone)) => (addr, Some(zone)),
+        None => (value, None),
+    };
+    let addr = addr.parse::<Ipv6Addr>().ok()?;
+    Some(match zone {
+        Some(zone) => format!("{addr}%{zone}"),
+        None => addr.to_string(),
+    })
+}
+
+/// the spellings of each month accepted by the `MONTH` pattern, including the German ones.
+const MONTHS: [&[&str]; 12] = [
+    &["jan", "january", "januar"],
+    &["feb", "february", "februar"],
+    &["mar", "march", "marz", "mär", "märz", "mrz"],
+    &["apr", "april"],
+    &["may", "mai"],
+    &["jun", "june", "juni"],
+    &["jul", "july", "juli"],
+    &["aug", "august"],
+    &["sep", "sept", "september"],
+    &["oct", "october", "okt", "oktober"],
+    &["nov", "november"],
+    &["dec", "december", "dez", "dezember"],
+];
+
+const DAYS: [&[&str]; 7] = [
+    &["mon", "monday"],
+    &["tue", "tuesday"],
+    &["wed", "wednesday"],
+    &["thu", "thursday"],
+    &["fri", "friday"],
+    &["sat", "saturday"],
+    &["sun", "sunday"],
+];
+
+/// the number of the month, 1-12, either from the name or the number.
+fn month_number(value: &str) -> Option<i64> {
+    lookup(value, &MONTHS)
+}
+
+/// the ISO 8601 number of the day of week, 1 (Monday) - 7 (Sunday), either from the name or the number.
+fn day_number(value: &str) -> Option<i64> {
+    lookup(value, &DAYS)
+}
+
+fn lookup(value: &str, names: &[&[&str]]) -> Option<i64> {
+    if let Ok(n) = value.parse::<usize>() {
+        return (1..=names.len()).contains(&n).then_some(n as i64);
+    }
+
+    let value = value.to_lowercase();
+    names
+        .iter()
+        .position(|spellings| spellings.contains(&value.as_str()))
+        .map(|i| i as i64 + 1)
+}
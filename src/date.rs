@@ -0,0 +1,138 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+/// the order of the day and the month in a numeric date, e.g. `04/05/2022`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DateOrder {
+    /// month first, e.g. `04/21/2022`.
+    Us,
+    /// day first, e.g. `21.04.2022`.
+    Eu,
+    /// decided by the value, only if it's not ambiguous, e.g. `21/04/2022`.
+    Auto,
+}
+
+/// the date type with its order argument, e.g. `date`, `date(us)`, or `date(eu)`.
+fn date_order(type_: &str) -> Option<DateOrder> {
+    match type_ {
+        "date" => Some(DateOrder::Auto),
+        "date(us)" => Some(DateOrder::Us),
+        "date(eu)" => Some(DateOrder::Eu),
+        _ => None,
+    }
+}
+
+pub(crate) fn is_date_type(type_: &str) -> bool {
+    date_order(type_).is_some()
+}
+
+/// parse the numeric date matched by `DATE_US` or `DATE_EU`, the time is midnight.
+/// the two-digit year is in 1970-2069.
+pub(crate) fn parse_date(value: &str, type_: &str) -> Result<NaiveDateTime, &'static str> {
+    let order = date_order(type_).ok_or("unknown date order")?;
+
+    let parts = value
+        .split(['.', '/', '-'])
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()
+        .ok_or("invalid date")?;
+    let [a, b, year] = parts[..] else {
+        return Err("invalid date");
+    };
+
+    let year = match value.rsplit(['.', '/', '-']).next().map(str::len) {
+        Some(2) if year < 70 => 2000 + year,
+        Some(2) => 1900 + year,
+        Some(4) => year,
+        _ => return Err("invalid date"),
+    };
+
+    let (month, day) = match order {
+        DateOrder::Us => (a, b),
+        DateOrder::Eu => (b, a),
+        DateOrder::Auto if a == b || b > 12 => (a, b),
+        DateOrder::Auto if a > 12 => (b, a),
+        DateOrder::Auto => return Err("ambiguous date"),
+    };
+
+    NaiveDate::from_ymd_opt(year as i32, month, day)
+        .map(|date| date.and_time(NaiveTime::MIN))
+        .ok_or("invalid date")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Grok, GrokError, Value};
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Value {
+        Value::DateTime(
+            NaiveDate::from_ymd_opt(year, month, day)
+                .unwrap()
+                .and_time(NaiveTime::MIN),
+        )
+    }
+
+    #[test]
+    fn test_date_type() {
+        let grok = Grok::default();
+        let us = grok.compile("%{DATE:d:date(us)}", true).unwrap();
+        let eu = grok.compile("%{DATE:d:date(eu)}", true).unwrap();
+        let auto = grok.compile("%{DATE:d:date}", true).unwrap();
+
+        // the same ambiguous string
+        assert_eq!(
+            Some(&date(2022, 4, 5)),
+            us.parse("04/05/2022").unwrap().get("d")
+        );
+        assert_eq!(
+            Some(&date(2022, 5, 4)),
+            eu.parse("04/05/2022").unwrap().get("d")
+        );
+        assert_eq!(
+            Err(GrokError::Conversion {
+                value: "04/05/2022".to_string(),
+                reason: "ambiguous date".to_string(),
+            }),
+            auto.parse("04/05/2022")
+        );
+
+        assert_eq!(
+            Some(&date(2022, 4, 21)),
+            eu.parse("21.04.2022").unwrap().get("d")
+        );
+        assert_eq!(
+            Some(&date(2022, 4, 21)),
+            auto.parse("21.04.2022").unwrap().get("d")
+        );
+        assert_eq!(
+            Some(&date(2022, 4, 21)),
+            auto.parse("04/21/2022").unwrap().get("d")
+        );
+        assert_eq!(
+            Some(&date(1999, 12, 31)),
+            us.parse("12-31-99").unwrap().get("d")
+        );
+        assert_eq!(
+            Some(&date(2022, 4, 4)),
+            auto.parse("4/4/22").unwrap().get("d")
+        );
+
+        // month 21
+        assert_eq!(
+            Err(GrokError::Conversion {
+                value: "21/04/2022".to_string(),
+                reason: "invalid date".to_string(),
+            }),
+            grok.compile("%{NOTSPACE:d:date(us)}", true)
+                .unwrap()
+                .parse("21/04/2022")
+        );
+        assert!(eu.parse("31.02.2022").is_err());
+
+        assert_eq!(
+            Err(GrokError::UnknownType("date(fr)".to_string())),
+            grok.compile("%{DATE:d:date(fr)}", true).map(|_| ())
+        );
+    }
+}
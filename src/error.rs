@@ -0,0 +1,83 @@
+use std::fmt;
+
+/// The error returned when compiling a pattern or parsing an input fails.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GrokError {
+    /// the referenced pattern is neither user-defined nor a default pattern.
+    PatternNotFound(String),
+    /// the pattern is nested deeper than the max rounds of the expansion, every round replaces all the
+    /// occurrences of a placeholder, or the pattern references itself, e.g. `A x%{A}`.
+    RecursionLimit(i32),
+    /// the expanded regex can't be compiled.
+    Regex(String),
+    /// the captured value can't be converted to the specified type.
+    Conversion { value: String, reason: String },
+    /// the text starts like a placeholder (`%{`), but is not a valid `%{PATTERN:alias:type}`.
+    /// the position is the byte offset of `%{` in the expression, or in the definition of the
+    /// referenced pattern which contains it.
+    MalformedPlaceholder { text: String, position: usize },
+    /// the field required by [`FromGrok`](crate::FromGrok) is not captured.
+    MissingField(String),
+    /// the `key=value` pair can't be split, see [`parse_kv`](crate::parse_kv).
+    MalformedPair { text: String, position: usize },
+    /// the line of the pattern definitions is not `NAME regex`.
+    MalformedDefinition(String),
+    /// the structured pattern definitions, e.g. TOML or YAML, can't be deserialized.
+    InvalidConfig(String),
+    /// the [`CompileOptions`](crate::CompileOptions) can't be applied, e.g. `max_array_len` of 0.
+    InvalidOption(String),
+    /// the type is neither built-in nor a registered alias of a built-in type.
+    UnknownType(String),
+    /// reading the input or writing the output fails.
+    Io(String),
+    /// the field is captured more times than `max_array_len`, see [`CompileOptions::max_array_len`](crate::CompileOptions::max_array_len).
+    ArrayTooLong { field: String, max: usize },
+    /// the capture group name is used twice in the expanded regex, or an unaliased group has the
+    /// same name as the alias of a placeholder.
+    DuplicateGroup { name: String },
+    /// the `@key` and `@value` pair of the field can't be collected into a map,
+    /// see [`Grok::compile_bytes`](crate::Grok::compile_bytes).
+    UnsupportedMap { field: String },
+    /// the input can't be matched by the pattern.
+    NoMatch,
+    /// only the range `start..end` of the input is matched, but the whole input is required.
+    PartialMatch { start: usize, end: usize },
+}
+
+impl fmt::Display for GrokError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrokError::PatternNotFound(name) => write!(f, "pattern: {name} not found"),
+            GrokError::RecursionLimit(max) => write!(f, "max recursion {max} reached"),
+            GrokError::Regex(e) => write!(f, "{e}"),
+            GrokError::Conversion { value, reason } => write!(f, "{reason}: {value}"),
+            GrokError::MalformedPlaceholder { text, position } => {
+                write!(f, "malformed placeholder `{text}` at position {position}")
+            }
+            GrokError::MissingField(name) => write!(f, "field: {name} not captured"),
+            GrokError::MalformedPair { text, position } => {
+                write!(f, "malformed pair `{text}` at position {position}")
+            }
+            GrokError::MalformedDefinition(line) => {
+                write!(f, "malformed pattern definition: {line}")
+            }
+            GrokError::InvalidConfig(e) => write!(f, "invalid config: {e}"),
+            GrokError::InvalidOption(e) => write!(f, "invalid option: {e}"),
+            GrokError::UnknownType(type_) => write!(f, "type: {type_} not supported"),
+            GrokError::Io(e) => write!(f, "{e}"),
+            GrokError::ArrayTooLong { field, max } => {
+                write!(f, "field: {field} captured more than {max} times")
+            }
+            GrokError::DuplicateGroup { name } => write!(f, "duplicate capture group: {name}"),
+            GrokError::UnsupportedMap { field } => {
+                write!(f, "map field: {field} not supported by the bytes pattern")
+            }
+            GrokError::NoMatch => write!(f, "no match"),
+            GrokError::PartialMatch { start, end } => {
+                write!(f, "only {start}..{end} of the input matched")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GrokError {}
@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use crate::{GrokError, Value};
+
+/// Build a typed value from the parsed map, see [`Pattern::parse_into`](crate::Pattern::parse_into).
+///
+/// It can be derived by `#[derive(FromGrok)]` with the `derive` feature enabled, every field is
+/// populated by its name, or by `#[grok(rename = "destination.ip")]` for dotted aliases.
+pub trait FromGrok: Sized {
+    fn from_grok(map: &HashMap<String, Value>) -> Result<Self, GrokError>;
+}
+
+/// Convert a parsed [`Value`] into a Rust type, it's used by the derived [`FromGrok`].
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, GrokError>;
+
+    /// convert the value of the field, if the field is not captured, then `MissingField` will be returned.
+    fn from_field(map: &HashMap<String, Value>, name: &str) -> Result<Self, GrokError> {
+        match map.get(name) {
+            Some(value) => Self::from_value(value),
+            None => Err(GrokError::MissingField(name.to_string())),
+        }
+    }
+}
+
+fn conversion_error(value: &Value, reason: impl ToString) -> GrokError {
+    let value = match value {
+        Value::Int(v) => v.to_string(),
+        Value::Float(v) => v.to_string(),
+        Value::Bool(v) => v.to_string(),
+        Value::String(v) => v.clone(),
+        Value::Array(v) => format!("{v:?}"),
+        Value::Map(v) => format!("{v:?}"),
+        Value::Null => "null".to_string(),
+        Value::Bytes(v) => String::from_utf8_lossy(v).into_owned(),
+        #[cfg(feature = "chrono")]
+        Value::DateTime(v) => v.to_string(),
+    };
+    GrokError::Conversion {
+        value,
+        reason: reason.to_string(),
+    }
+}
+
+macro_rules! from_value_int {
+    ($($t:ty),*) => {
+        $(
+            impl FromValue for $t {
+                fn from_value(value: &Value) -> Result<Self, GrokError> {
+                    match value {
+                        Value::Int(v) => <$t>::try_from(*v).map_err(|e| conversion_error(value, e)),
+                        Value::String(v) => v.parse::<$t>().map_err(|e| conversion_error(value, e)),
+                        _ => Err(conversion_error(value, concat!("not a ", stringify!($t)))),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_value_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! from_value_float {
+    ($($t:ty),*) => {
+        $(
+            impl FromValue for $t {
+                fn from_value(value: &Value) -> Result<Self, GrokError> {
+                    match value {
+                        Value::Float(v) => Ok(*v as $t),
+                        Value::Int(v) => Ok(*v as $t),
+                        Value::String(v) => v.parse::<$t>().map_err(|e| conversion_error(value, e)),
+                        _ => Err(conversion_error(value, concat!("not a ", stringify!($t)))),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_value_float!(f32, f64);
+
+macro_rules! from_value_parse {
+    ($($t:ty),*) => {
+        $(
+            impl FromValue for $t {
+                fn from_value(value: &Value) -> Result<Self, GrokError> {
+                    match value {
+                        Value::String(v) => v.parse::<$t>().map_err(|e| conversion_error(value, e)),
+                        _ => Err(conversion_error(value, concat!("not a ", stringify!($t)))),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_value_parse!(IpAddr, Ipv4Addr, Ipv6Addr);
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, GrokError> {
+        match value {
+            Value::Bool(v) => Ok(*v),
+            Value::String(v) => v.parse::<bool>().map_err(|e| conversion_error(value, e)),
+            _ => Err(conversion_error(value, "not a bool")),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, GrokError> {
+        match value {
+            Value::String(v) => Ok(v.clone()),
+            Value::Int(v) => Ok(v.to_string()),
+            Value::Float(v) => Ok(v.to_string()),
+            Value::Bool(v) => Ok(v.to_string()),
+            Value::Bytes(v) => String::from_utf8(v.clone()).map_err(|e| conversion_error(value, e)),
+            #[cfg(feature = "chrono")]
+            Value::DateTime(v) => Ok(v.to_string()),
+            Value::Array(_) | Value::Map(_) | Value::Null => {
+                Err(conversion_error(value, "not a string"))
+            }
+        }
+    }
+}
+
+/// the field captured several times, a single value is converted to a one-element vector.
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Result<Self, GrokError> {
+        match value {
+            Value::Array(values) => values.iter().map(T::from_value).collect(),
+            _ => T::from_value(value).map(|v| vec![v]),
+        }
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self, GrokError> {
+        Ok(value.clone())
+    }
+}
+
+/// the field is optional, `None` if it's not captured or it's [`Value::Null`].
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self, GrokError> {
+        match value {
+            Value::Null => Ok(None),
+            _ => T::from_value(value).map(Some),
+        }
+    }
+
+    fn from_field(map: &HashMap<String, Value>, name: &str) -> Result<Self, GrokError> {
+        map.get(name).map_or(Ok(None), Self::from_value)
+    }
+}
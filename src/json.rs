@@ -0,0 +1,403 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde_json::{json, Map};
+
+use crate::{GrokError, Pattern, Value};
+
+/// the value is serialized as the plain JSON value, e.g. `Value::Int(1)` as `1`.
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Int(v) => serializer.serialize_i64(*v),
+            Value::Float(v) => serializer.serialize_f64(*v),
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Array(v) => v.serialize(serializer),
+            Value::Map(pairs) => {
+                let mut map = serializer.serialize_map(Some(pairs.len()))?;
+                for (k, v) in pairs {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+            Value::Null => serializer.serialize_none(),
+            Value::Bytes(v) => serializer.serialize_bytes(v),
+            #[cfg(feature = "chrono")]
+            Value::DateTime(v) => serializer.collect_str(&v.format("%Y-%m-%dT%H:%M:%S")),
+        }
+    }
+}
+
+/// What to write for the line which can't be matched by [`Pattern::parse_to_writer`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Unmatched {
+    /// skip the line.
+    #[default]
+    Skip,
+    /// write an empty object `{}`.
+    Empty,
+}
+
+impl Pattern {
+    /// parse every line of the reader, and write one JSON object per line to the writer, i.e. JSON Lines.
+    /// return the count of the lines which are matched.
+    ///  - the unmatched lines are skipped or written as `{}` according to `unmatched`.
+    ///  - if the value can't be converted to the specified type, then the error will be returned.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::{Grok, Unmatched};
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{INT:status:int}", true).unwrap();
+    /// let mut output = Vec::new();
+    /// let count = pattern
+    ///     .parse_to_writer("200\n-\n".as_bytes(), &mut output, Unmatched::Skip)
+    ///     .unwrap();
+    /// assert_eq!(1, count);
+    /// assert_eq!("{\"status\":200}\n", String::from_utf8(output).unwrap());
+    /// ```
+    pub fn parse_to_writer<R: BufRead, W: Write>(
+        &self,
+        reader: R,
+        writer: &mut W,
+        unmatched: Unmatched,
+    ) -> Result<usize, GrokError> {
+        let mut count = 0;
+
+        for line in reader.lines() {
+            let line = line.map_err(|e| GrokError::Io(e.to_string()))?;
+            // the matched line may capture nothing, e.g. `%{WORD}`, which is still counted
+            let map = match self.regex.captures(&line) {
+                Some(caps) => {
+                    count += 1;
+                    self.extract(&line, &caps)?
+                }
+                None if unmatched == Unmatched::Skip => continue,
+                None => HashMap::new(),
+            };
+
+            serde_json::to_writer(&mut *writer, &map).map_err(|e| GrokError::Io(e.to_string()))?;
+            writer
+                .write_all(b"\n")
+                .map_err(|e| GrokError::Io(e.to_string()))?;
+        }
+
+        Ok(count)
+    }
+
+    /// the JSON Schema of the object produced by [`Pattern::parse`], every field of [`Pattern::schema`]
+    /// is a property typed by its `:type` suffix, and the dotted fields are nested,
+    /// e.g. `destination.ip` is the property `ip` of the object `destination`.
+    ///
+    /// Notice the parsed map, and so [`Pattern::parse_to_writer`], keeps the dotted keys flat,
+    /// e.g. `{"destination.ip": ".."}`, nest them before validating the output against the schema.
+    ///
+    /// The field whose type is inferred by [`CompileOptions::infer_types_by_name`](crate::CompileOptions::infer_types_by_name)
+    /// may be a string as well, and the field with a default value, see [`CompileOptions::defaults`](crate::CompileOptions::defaults),
+    /// may be of the type of the default value.
+    ///
+    /// The field collected by [`CompileOptions::collect_arrays`](crate::CompileOptions::collect_arrays)
+    /// is either its type or an array of it, since the field captured only once is a single value.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{NUMBER:n:int} %{WORD:w}", true).unwrap();
+    /// let schema = pattern.json_schema();
+    /// assert_eq!("integer", schema["properties"]["n"]["type"]);
+    /// assert_eq!("string", schema["properties"]["w"]["type"]);
+    /// ```
+    pub fn json_schema(&self) -> serde_json::Value {
+        let mut properties = Map::new();
+
+        for field in self.schema() {
+            let types = self.json_types(&field.name, field.type_.as_deref());
+            let mut property = if self.is_collected(&field.name) {
+                let mut collected = types.clone();
+                collected.push("array");
+                json!({"type": collected, "items": {"type": one_or_many(types)}})
+            } else {
+                json!({"type": one_or_many(types)})
+            };
+            if let Some(description) = field.description {
+                property["description"] = description.into();
+            }
+
+            let mut segments = field.name.split('.').peekable();
+            let mut parent = &mut properties;
+            while let Some(segment) = segments.next() {
+                if segments.peek().is_none() {
+                    parent.entry(segment).or_insert(property);
+                    break;
+                }
+                let object = parent
+                    .entry(segment)
+                    .or_insert_with(|| json!({"type": "object", "properties": {}}));
+                // the field is already a leaf, e.g. both `a` and `a.b` are captured
+                match object.get_mut("properties").and_then(|p| p.as_object_mut()) {
+                    Some(properties) => parent = properties,
+                    None => break,
+                }
+            }
+        }
+
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": properties,
+        })
+    }
+}
+
+impl Pattern {
+    /// the JSON types of the field, the type of the captured value first.
+    fn json_types(&self, field: &str, type_: Option<&str>) -> Vec<&'static str> {
+        let mut types = Vec::new();
+        if self.maps.iter().any(|group| *group.field == *field) {
+            types.push("object");
+        } else if self.keys.values().any(|key| **key == *field)
+            || self.original_field.as_deref() == Some(field)
+        {
+            types.push(type_.map_or("string", json_type));
+        }
+        // the value which can't be converted to the inferred type is kept as string
+        if self
+            .keys
+            .iter()
+            .any(|(group, key)| **key == *field && self.inferred.contains(group))
+        {
+            types.push("string");
+        }
+        if let Some(value) = self.defaults.get(field) {
+            types.push(value_type(value));
+        }
+
+        let mut unique = Vec::with_capacity(types.len());
+        for type_ in types {
+            if !unique.contains(&type_) {
+                unique.push(type_);
+            }
+        }
+        unique
+    }
+
+    /// the field is captured by several groups, so it may be collected into an array.
+    fn is_collected(&self, field: &str) -> bool {
+        self.collect_arrays && self.keys.values().filter(|key| ***key == *field).count() > 1
+    }
+}
+
+/// the single type as a string, or the types as an array.
+fn one_or_many(types: Vec<&str>) -> serde_json::Value {
+    match types.as_slice() {
+        [type_] => json!(type_),
+        _ => json!(types),
+    }
+}
+
+/// the JSON type of the value serialized by [`Value`]'s `Serialize`, e.g. of a default value.
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Int(_) => "integer",
+        Value::Float(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::String(_) => "string",
+        Value::Array(_) | Value::Bytes(_) => "array",
+        Value::Map(_) => "object",
+        Value::Null => "null",
+        #[cfg(feature = "chrono")]
+        Value::DateTime(_) => "string",
+    }
+}
+
+/// the JSON type of the converted value, the types converted to strings, e.g. `ip`, are `string`.
+fn json_type(type_: &str) -> &'static str {
+    match type_ {
+        "int" | "long" | "monthnum" | "daynum" => "integer",
+        "float" | "double" => "number",
+        "bool" | "boolean" => "boolean",
+        _ => "string",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CompileOptions, Grok};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_to_writer() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile("%{IP:client} %{WORD:method} %{NUMBER:bytes:int}", true)
+            .unwrap();
+        let input = "55.3.244.1 GET 15824\nnot matched\n10.0.0.1 POST 7\n";
+
+        let lines = |output: Vec<u8>| {
+            String::from_utf8(output)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let mut output = Vec::new();
+        let count = pattern
+            .parse_to_writer(input.as_bytes(), &mut output, Unmatched::Skip)
+            .unwrap();
+        assert_eq!(2, count);
+        assert_eq!(
+            vec![
+                serde_json::json!({"client": "55.3.244.1", "method": "GET", "bytes": 15824}),
+                serde_json::json!({"client": "10.0.0.1", "method": "POST", "bytes": 7}),
+            ],
+            lines(output)
+        );
+
+        let mut output = Vec::new();
+        let count = pattern
+            .parse_to_writer(input.as_bytes(), &mut output, Unmatched::Empty)
+            .unwrap();
+        assert_eq!(2, count);
+        let output = lines(output);
+        assert_eq!(3, output.len());
+        assert_eq!(serde_json::json!({}), output[1]);
+
+        let pattern = grok.compile("%{WORD}", true).unwrap();
+        let mut output = Vec::new();
+        let count = pattern
+            .parse_to_writer(
+                "GET
+-
+"
+                .as_bytes(),
+                &mut output,
+                Unmatched::Skip,
+            )
+            .unwrap();
+        assert_eq!(1, count);
+        assert_eq!("{}\n", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let grok = Grok::default();
+        let pattern = grok.compile("%{NUMBER:n:int} %{WORD:w}", true).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "n": {"type": "integer"},
+                    "w": {"type": "string"},
+                },
+            }),
+            pattern.json_schema()
+        );
+
+        let pattern = grok
+            .compile(
+                r#"%{IP:destination.ip:ip} %{INT:destination.port:int:"the port"} %{NUMBER:took:float} (?:%{WORD:@key}=%{WORD:@value},?)*"#,
+                true,
+            )
+            .unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "destination": {
+                    "type": "object",
+                    "properties": {
+                        "ip": {"type": "string"},
+                        "port": {"type": "integer", "description": "the port"},
+                    },
+                },
+                "took": {"type": "number"},
+                "kv": {"type": "object"},
+            }),
+            pattern.json_schema()["properties"]
+        );
+    }
+
+    #[test]
+    fn test_json_schema_arrays() {
+        let grok = Grok::default();
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .collect_arrays(true);
+        let pattern = grok
+            .compile_with_opts("%{INT:n:int} %{INT:n:int} %{WORD:w}", &opts)
+            .unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "n": {"type": ["integer", "array"], "items": {"type": "integer"}},
+                "w": {"type": "string"},
+            }),
+            pattern.json_schema()["properties"]
+        );
+
+        // the last one wins without collect_arrays
+        let pattern = grok.compile("%{INT:n:int} %{INT:n:int}", true).unwrap();
+        assert_eq!(
+            serde_json::json!({"n": {"type": "integer"}}),
+            pattern.json_schema()["properties"]
+        );
+    }
+
+    #[test]
+    fn test_json_schema_defaults_and_inferred() {
+        let grok = Grok::default();
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .infer_types_by_name(true)
+            .default_value("port", Value::Int(80))
+            .default_value("ip", Value::Null);
+        let pattern = grok
+            .compile_with_opts("(?:%{IP:ip}|%{HOSTNAME:host}) %{NUMBER:dest.port}", &opts)
+            .unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "ip": {"type": ["string", "null"]},
+                "host": {"type": "string"},
+                "dest": {
+                    "type": "object",
+                    "properties": {
+                        "port": {"type": ["integer", "string"]},
+                    },
+                },
+                "port": {"type": "integer"},
+            }),
+            pattern.json_schema()["properties"]
+        );
+
+        // the output matches the schema
+        let result = pattern.parse("example.com 1.5").unwrap();
+        assert_eq!(Some(&Value::Int(80)), result.get("port"));
+        assert_eq!(
+            Some(&Value::String("1.5".to_string())),
+            result.get("dest.port")
+        );
+    }
+
+    #[test]
+    fn test_serialize_value() {
+        let values = [
+            Value::Int(-1),
+            Value::Float(0.5),
+            Value::Bool(true),
+            Value::String("a".to_string()),
+            Value::Array(vec![Value::Int(1), Value::String("b".to_string())]),
+            Value::Null,
+        ];
+        assert_eq!(
+            r#"[-1,0.5,true,"a",[1,"b"],null]"#,
+            serde_json::to_string(&values).unwrap()
+        );
+    }
+}
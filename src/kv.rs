@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use crate::{GrokError, Value};
+
+/// Options used by [`parse_kv`] to split the `key=value` pairs.
+///
+/// # Example
+/// ```
+/// use grok_rs::KvOptions;
+///
+/// let opts = KvOptions::default().field_split(",").value_split(":");
+/// ```
+#[derive(Debug, Clone)]
+pub struct KvOptions {
+    /// the separator between the pairs, a space by default.
+    pub field_split: String,
+    /// the separator between the key and the value, `=` by default.
+    pub value_split: String,
+    /// if true, then the repeated and trailing field separators are collapsed, e.g. `a=1,,b=2,`.
+    /// otherwise the empty pair between them is an error. true by default.
+    pub collapse_separators: bool,
+}
+
+impl Default for KvOptions {
+    fn default() -> Self {
+        Self {
+            field_split: " ".to_string(),
+            value_split: "=".to_string(),
+            collapse_separators: true,
+        }
+    }
+}
+
+impl KvOptions {
+    pub fn field_split<T: Into<String>>(mut self, sep: T) -> Self {
+        self.field_split = sep.into();
+        self
+    }
+
+    pub fn value_split<T: Into<String>>(mut self, sep: T) -> Self {
+        self.value_split = sep.into();
+        self
+    }
+
+    pub fn collapse_separators(mut self, yes: bool) -> Self {
+        self.collapse_separators = yes;
+        self
+    }
+}
+
+/// parse the `key=value` pairs of the input string.
+///  - the value is kept as string, and the later pair wins if the key is duplicated.
+///  - the pair with an empty key, e.g. `=1`, is skipped.
+///  - the pair without the value separator is an error.
+///  - the empty pair is an error unless `collapse_separators` is true.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use grok_rs::{parse_kv, KvOptions, Value};
+///
+/// let opts = KvOptions::default().field_split(",");
+/// let result = parse_kv("a=1,,b=2,", &opts).unwrap();
+/// let expected = HashMap::from([
+///     ("a".to_string(), Value::String("1".into())),
+///     ("b".to_string(), Value::String("2".into())),
+/// ]);
+/// assert_eq!(expected, result);
+/// ```
+pub fn parse_kv(s: &str, opts: &KvOptions) -> Result<HashMap<String, Value>, GrokError> {
+    let mut map = HashMap::new();
+    let mut position = 0;
+
+    for pair in s.split(opts.field_split.as_str()) {
+        let start = position;
+        position += pair.len() + opts.field_split.len();
+
+        if pair.is_empty() {
+            if opts.collapse_separators {
+                continue;
+            }
+            return Err(GrokError::MalformedPair {
+                text: pair.to_string(),
+                position: start,
+            });
+        }
+
+        let (key, value) = match pair.split_once(opts.value_split.as_str()) {
+            Some(kv) => kv,
+            None => {
+                return Err(GrokError::MalformedPair {
+                    text: pair.to_string(),
+                    position: start,
+                })
+            }
+        };
+        if key.is_empty() {
+            continue;
+        }
+
+        map.insert(key.to_string(), Value::String(value.to_string()));
+    }
+
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expected(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_kv() {
+        let opts = KvOptions::default();
+        assert_eq!(
+            expected(&[("a", "1"), ("b", "2")]),
+            parse_kv("a=1 b=2", &opts).unwrap()
+        );
+        // the later pair wins, and the value may contain the value separator
+        assert_eq!(
+            expected(&[("a", "2"), ("b", "x=y")]),
+            parse_kv("a=1 a=2 b=x=y", &opts).unwrap()
+        );
+        // the empty key is skipped
+        assert_eq!(expected(&[("b", "")]), parse_kv("=1 b=", &opts).unwrap());
+        assert!(parse_kv("", &opts).unwrap().is_empty());
+
+        assert_eq!(
+            Err(GrokError::MalformedPair {
+                text: "b".to_string(),
+                position: 4,
+            }),
+            parse_kv("a=1 b", &opts)
+        );
+    }
+
+    #[test]
+    fn test_parse_kv_irregular_spacing() {
+        let opts = KvOptions::default();
+        assert_eq!(
+            expected(&[("a", "1"), ("b", "2")]),
+            parse_kv("  a=1   b=2 ", &opts).unwrap()
+        );
+
+        let opts = opts.collapse_separators(false);
+        assert_eq!(
+            Err(GrokError::MalformedPair {
+                text: "".to_string(),
+                position: 4,
+            }),
+            parse_kv("a=1  b=2", &opts)
+        );
+    }
+
+    #[test]
+    fn test_parse_kv_trailing_separator() {
+        let opts = KvOptions::default().field_split(",").value_split(":");
+        assert_eq!(
+            expected(&[("a", "1"), ("b", "2")]),
+            parse_kv("a:1,,b:2,", &opts).unwrap()
+        );
+
+        let opts = KvOptions::default().field_split(",");
+        assert_eq!(
+            expected(&[("a", "1"), ("b", "2")]),
+            parse_kv("a=1,,b=2,", &opts).unwrap()
+        );
+        assert!(parse_kv("a=1,b=2,", &opts.collapse_separators(false)).is_err());
+    }
+}
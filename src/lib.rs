@@ -9,8 +9,24 @@
 //! - double
 //! - bool
 //! - boolean
+//! - monthnum, the month in any spelling of `MONTH` or `MONTHNUM` is normalized to 1-12, e.g. `March`, `Mar`, `03`
+//! - daynum, the day of week in any spelling of `DAY` is normalized to 1 (Monday) - 7 (Sunday)
+//! - ip, the IPv6 address is normalized to the compressed and lowercase form, e.g. `2001:db8::1`, IPv4 is kept as is
+//! - hex, the hex digits, e.g. of a hash, are lowercased, `hex(len)` requires exactly `len` digits, e.g. `hex(64)` for SHA-256
+//! - date, the numeric date of `DATE` is converted to [`Value::DateTime`], requires the `chrono` feature.
+//!   `date(us)` reads the month first, `date(eu)` reads the day first, and `date` only accepts the unambiguous dates,
+//!   e.g. `21/04/2022`
 //!
 //! If the type is not specified, then the value will be kept as string.
+//! A quoted description can follow the alias or the type, e.g. `%{NUMBER:bytes:int:"response size"}`,
+//! it only documents the field in [`Pattern::schema`] and doesn't affect the matching.
+//! More names of the types can be registered by [`Grok::register_type_alias`].
+//!
+//! The `@key` and `@value` aliases collect the repeated pairs into a [`Value::Map`], e.g.
+//! `^(?:%{WORD:@key}=%{DATA:@value}\s*)+$` parses `a=1 b=2` into the field `kv`,
+//! and `%{WORD:labels@key}`/`%{INT:labels@value:int}` into the field `labels`.
+//! A lazy value like `DATA` only extends as far as the rest of the pattern requires, so without the anchors
+//! the repetition stops after `a=`, use a value which can't be empty, e.g. `NOTSPACE`, or anchor the pattern.
 //!
 //! # Usage
 //!
@@ -30,45 +46,206 @@
 //! assert_eq!(expected, pattern.parse("admin user").unwrap());
 //! ```
 use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
+    collections::{hash_map::Entry, HashMap, HashSet},
+    hash::Hash,
+    sync::{Arc, OnceLock},
 };
 
-use regex::Regex;
-
+use convert::convert;
+use regex::{Captures, Regex};
+
+mod bytes;
+mod clf;
+#[cfg(any(feature = "toml", feature = "yaml"))]
+mod config;
+mod convert;
+#[cfg(feature = "chrono")]
+mod date;
+mod error;
+mod from_grok;
+#[cfg(feature = "serde")]
+mod json;
+mod kv;
+mod lines;
+mod map;
+mod multi;
+mod source;
+mod suggest;
+
+pub use bytes::BytesPattern;
+pub use clf::{parse_clf, ClfRecord};
+pub use error::GrokError;
+pub use from_grok::{FromGrok, FromValue};
+#[cfg(feature = "derive")]
+pub use grok_rs_derive::FromGrok;
+#[cfg(feature = "serde")]
+pub use json::Unmatched;
+pub use kv::{parse_kv, KvOptions};
+pub use lines::LinesReport;
+pub use multi::{MultiMatch, MultiPattern};
+pub use source::{DefaultSource, DirSource, PatternSource, ReaderSource};
+
+/// the max rounds of the expansion, every round replaces all the occurrences of a placeholder, e.g. `%{WORD:a}`.
 const MAX_RECURSION: i32 = 1024;
 
 const NAME_INDEX: usize = 1;
 const PATTERN_INDEX: usize = 2;
 const ALIAS_INDEX: usize = 3;
 const TYPE_INDEX: usize = 4;
+const DESCRIPTION_INDEX: usize = 5;
 
-const GROK_PATTERN: &str = r"(?x)
+const GROK_PATTERN: &str = r#"(?x)
 %\{
     (?<name>
-        (?<pattern>[[:word:]]+)
+        (?<pattern>[[:word:]]+(?:::[[:word:]]+)?)
         (?:
             :(?<alias>[[[:word:]]@.-]+)
             (?:
-                :(?<type>int|long|float|double|bool(?:ean)?)
+                :(?<type>[[:word:]]+(?:\([[:word:]]*\))?)
+            )?
+            (?:
+                :"(?<description>[^"]*)"
             )?
         )?
     )
-\}";
+\}"#;
+
+/// check that every `%{` in the expression starts a valid placeholder.
+fn check_placeholders(s: &str) -> Result<(), GrokError> {
+    for (position, _) in s.match_indices("%{") {
+        match GROK_REGEX.find_at(s, position) {
+            Some(m) if m.start() == position => continue,
+            _ => {
+                let text = match s[position..].find('}') {
+                    Some(end) => &s[position..=position + end],
+                    None => &s[position..],
+                };
+                return Err(GrokError::MalformedPlaceholder {
+                    text: text.to_string(),
+                    position,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// the names of the named capture groups in the regex, in order, e.g. `(?<name>..)` or `(?P<name>..)`.
+/// the escaped parentheses and the ones in a character class are skipped.
+fn group_names(regex: &str) -> Vec<&str> {
+    let bytes = regex.as_bytes();
+    let mut names = Vec::new();
+    let mut in_class = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => in_class = true,
+            b']' => in_class = false,
+            b'(' if !in_class => {
+                let rest = &regex[i + 1..];
+                let prefix = ["?<", "?P<"].into_iter().find(|p| rest.starts_with(p));
+                if let Some(prefix) = prefix {
+                    let rest = &rest[prefix.len()..];
+                    if let Some(end) = rest.find('>') {
+                        let name = &rest[..end];
+                        if name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+                            names.push(name);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    names
+}
+
+/// count the capture groups and the alternations of the regex, the escaped characters and the ones
+/// in a character class are skipped.
+fn complexity_of(regex: &str) -> Complexity {
+    let bytes = regex.as_bytes();
+    let mut complexity = Complexity {
+        regex_len: regex.len(),
+        capture_groups: 0,
+        alternations: 0,
+    };
+    let mut in_class = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => in_class = true,
+            b']' => in_class = false,
+            b'|' if !in_class => complexity.alternations += 1,
+            b'(' if !in_class => {
+                let rest = &regex[i + 1..];
+                if !rest.starts_with('?') || rest.starts_with("?<") || rest.starts_with("?P<") {
+                    complexity.capture_groups += 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    complexity
+}
+
+/// check that every capture group name is unique in the regex, and that the ad-hoc groups,
+/// i.e. the ones without an alias, don't produce the same field as an aliased placeholder.
+fn check_duplicate_groups(
+    regex: &str,
+    alias: &HashMap<String, AliasType>,
+) -> Result<(), GrokError> {
+    let names = group_names(regex);
+    for (i, name) in names.iter().enumerate() {
+        let repeated = names[..i].contains(name);
+        let shadowed = !alias.contains_key(*name) && alias.values().any(|(field, _)| field == name);
+        if repeated || shadowed {
+            return Err(GrokError::DuplicateGroup {
+                name: name.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// the default pattern files embedded in the crate, in the order of the file names.
+const PATTERN_FILES: [&str; 22] = [
+    include_str!("patterns/aws"),
+    include_str!("patterns/bacula"),
+    include_str!("patterns/bind"),
+    include_str!("patterns/bro"),
+    include_str!("patterns/exim"),
+    include_str!("patterns/firewalls"),
+    include_str!("patterns/grok-patterns"),
+    include_str!("patterns/haproxy"),
+    include_str!("patterns/httpd"),
+    include_str!("patterns/java"),
+    include_str!("patterns/junos"),
+    include_str!("patterns/linux-syslog"),
+    include_str!("patterns/maven"),
+    include_str!("patterns/mcollective"),
+    include_str!("patterns/mongodb"),
+    include_str!("patterns/nagios"),
+    include_str!("patterns/postgresql"),
+    include_str!("patterns/rails"),
+    include_str!("patterns/redis"),
+    include_str!("patterns/ruby"),
+    include_str!("patterns/squid"),
+    include_str!("patterns/zeek"),
+];
 
 fn load_patterns() -> HashMap<String, String> {
     let mut patterns = HashMap::new();
 
-    for line in glob::glob("src/patterns/*")
-        .unwrap()
-        .map(|e| File::open(e.unwrap()).unwrap())
-        .flat_map(|f| BufReader::new(f).lines())
-        .map(|line| line.unwrap())
-        .filter(|line| !line.starts_with('#') && !line.is_empty())
-    {
-        let (key, value) = line.split_at(line.find(' ').unwrap());
-        patterns.insert(key.to_string(), value.trim().to_string());
+    for file in PATTERN_FILES {
+        patterns.extend(source::parse_patterns(file.as_bytes()).expect("valid default patterns"));
     }
 
     patterns.insert("BOOL".into(), "true|false".into());
@@ -78,6 +255,8 @@ fn load_patterns() -> HashMap<String, String> {
 
 lazy_static::lazy_static! {
     static ref GROK_REGEX: Regex = Regex::new(GROK_PATTERN).unwrap();
+    /// the ANSI CSI sequences, e.g. the color codes `\x1b[31m`.
+    static ref ANSI_REGEX: Regex = Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]").unwrap();
     static ref DEFAULT_PATTERNS: HashMap<String, String> = load_patterns();
 }
 
@@ -87,24 +266,179 @@ pub enum Value {
     Float(f64),
     Bool(bool),
     String(String),
+    /// the values of a field captured several times, see [`CompileOptions::collect_arrays`].
+    Array(Vec<Value>),
+    /// the field is not captured, see [`MissingPolicy::Null`].
+    Null,
+    /// the value captured by [`BytesPattern`], which may be invalid UTF-8.
+    Bytes(Vec<u8>),
+    /// the pairs captured by the `@key` and `@value` placeholders, in the order of the input,
+    /// e.g. `(?:%{WORD:@key}=%{NOTSPACE:@value}\s*)+`. The keys may repeat.
+    Map(Vec<(String, Value)>),
+    /// the date converted by the `date` type.
+    #[cfg(feature = "chrono")]
+    DateTime(chrono::NaiveDateTime),
+}
+
+/// How a field of the pattern is represented when it's not captured, see [`Pattern::parse_with_policy`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum MissingPolicy {
+    /// the field is not in the result, the same as [`Pattern::parse`].
+    #[default]
+    Omit,
+    /// the field is [`Value::Null`].
+    Null,
+    /// the field is the given value.
+    Default(Value),
+}
+
+/// the value is displayed as it's captured, e.g. `Value::String("GET")` as `GET`.
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Int(v) => write!(f, "{v}"),
+            Value::Float(v) => write!(f, "{v}"),
+            Value::Bool(v) => write!(f, "{v}"),
+            Value::String(v) => write!(f, "{v}"),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                write!(f, "]")
+            }
+            Value::Map(pairs) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{k}: {v}")?;
+                }
+                write!(f, "}}")
+            }
+            Value::Null => write!(f, "null"),
+            Value::Bytes(v) => write!(f, "{}", v.escape_ascii()),
+            #[cfg(feature = "chrono")]
+            Value::DateTime(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+/// format the parse result as a table of two columns, `field` and `value`, sorted by the field.
+/// the values are aligned after the longest field, e.g. for debugging.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use grok_rs::{format_table, Value};
+///
+/// let map = HashMap::from([("status".to_string(), Value::Int(200))]);
+/// assert_eq!("field   value\nstatus  200\n", format_table(&map));
+/// ```
+pub fn format_table(map: &HashMap<String, Value>) -> String {
+    let mut fields = map.iter().collect::<Vec<_>>();
+    fields.sort_by(|a, b| a.0.cmp(b.0));
+    let width = fields
+        .iter()
+        .map(|(k, _)| k.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("field".len());
+
+    let mut table = format!("{:<width$}  value\n", "field");
+    for (k, v) in fields {
+        table.push_str(&format!("{k:<width$}  {v}\n"));
+    }
+    table
 }
 
-type AliasType = (String, Option<String>);
+/// the alias and the type of a capture group.
+pub type AliasType = (String, Option<String>);
 
 #[derive(Debug)]
 pub struct Pattern {
     regex: Regex,
     alias: HashMap<String, AliasType>,
+    /// the interned output key of each capture group, shared across parses.
+    keys: HashMap<String, Arc<str>>,
+    /// the regex anchored at both ends, built on the first [`Pattern::validate`].
+    anchored: OnceLock<Result<Regex, GrokError>>,
+    /// the field to keep the whole input, see [`CompileOptions::keep_original_field`].
+    original_field: Option<Arc<str>>,
+    /// see [`CompileOptions::collect_arrays`].
+    collect_arrays: bool,
+    /// see [`CompileOptions::defaults`].
+    defaults: HashMap<Arc<str>, Value>,
+    /// the `@key` and `@value` pairs collected into [`Value::Map`].
+    maps: Vec<map::MapGroup>,
+    max_array_len: Option<usize>,
+    array_overflow: ArrayOverflow,
+    /// the description of each field, e.g. `%{NUMBER:bytes:int:"response size"}`.
+    descriptions: HashMap<String, String>,
+    /// the groups whose type is inferred from the alias, see [`CompileOptions::infer_types_by_name`].
+    inferred: HashSet<String>,
 }
 
 impl Pattern {
     fn new(regex: Regex, alias: HashMap<String, AliasType>) -> Self {
-        Self { regex, alias }
+        let keys = regex
+            .capture_names()
+            .flatten()
+            .map(|name| {
+                let key = alias.get(name).map_or(name, |(alias, _)| alias);
+                (name.to_string(), Arc::from(key))
+            })
+            .collect();
+
+        Self {
+            regex,
+            alias,
+            keys,
+            anchored: OnceLock::new(),
+            original_field: None,
+            collect_arrays: false,
+            defaults: HashMap::new(),
+            maps: Vec::new(),
+            inferred: HashSet::new(),
+            max_array_len: None,
+            array_overflow: ArrayOverflow::Error,
+            descriptions: HashMap::new(),
+        }
+    }
+
+    /// Compile the already expanded regex with the alias map, see [`Grok::expand`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use grok_rs::{Pattern, Value};
+    ///
+    /// let alias = HashMap::from([("name0".to_string(), ("code".to_string(), Some("int".to_string())))]);
+    /// let pattern = Pattern::compile_expanded(r"(?<name0>\d+)", alias).unwrap();
+    /// let expected = HashMap::from([("code".to_string(), Value::Int(200))]);
+    /// assert_eq!(expected, pattern.parse("200").unwrap());
+    /// ```
+    pub fn compile_expanded(
+        regex_str: &str,
+        alias: HashMap<String, AliasType>,
+    ) -> Result<Pattern, GrokError> {
+        check_duplicate_groups(regex_str, &alias)?;
+        let mut regex = regex_str.to_string();
+        let maps = map::prepare_maps(&mut regex, &alias)?;
+        let re = Regex::new(&regex).map_err(|e| GrokError::Regex(e.to_string()))?;
+        let mut pattern = Pattern::new(re, alias);
+        pattern.maps = maps;
+        Ok(pattern)
     }
 
     /// parse the input string based on the pattern, and rename the captured group based on alias.
-    ///  - if type is specified, then the value will be converted to the specified type.
-    ///  - if the type is not supported, then the value will be kept as string.
+    ///  - if type is specified, then the value will be converted to the specified type,
+    ///    the unknown type is already rejected when compiling by `UnknownType`.
     ///  - if the value can't be converted to the specified type, then an error will be returned.
     ///  - if the value can't be captured, then an empty map will be returned.
     ///
@@ -119,315 +453,2780 @@ impl Pattern {
     /// let expected = HashMap::from([("USERNAME".to_string(), Value::String("admin".into()))]);
     /// assert_eq!(expected, result);
     /// ```
-    pub fn parse(&self, s: &str) -> Result<HashMap<String, Value>, String> {
-        let mut map = HashMap::new();
-        let names = self.regex.capture_names().flatten().collect::<Vec<_>>();
+    pub fn parse(&self, s: &str) -> Result<HashMap<String, Value>, GrokError> {
+        match self.regex.captures(s) {
+            Some(caps) => self.extract(s, &caps),
+            None => Ok(HashMap::new()),
+        }
+    }
 
-        let caps = match self.regex.captures(s) {
-            Some(caps) => caps,
-            None => return Ok(map),
+    /// parse the input string like [`Pattern::parse`], then fill the fields which are not captured
+    /// by the policy of each field, e.g. a default port.
+    ///  - the fields without a policy are omitted.
+    ///  - the policy of a field the pattern never produces is ignored.
+    ///  - if the input can't be matched, then an empty map will be returned.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use grok_rs::{Grok, MissingPolicy, Value};
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{WORD:host}(:%{INT:port:int})?", true).unwrap();
+    /// let policy = HashMap::from([("port", MissingPolicy::Default(Value::Int(80)))]);
+    /// let result = pattern.parse_with_policy("localhost", &policy).unwrap();
+    /// assert_eq!(Some(&Value::Int(80)), result.get("port"));
+    /// ```
+    pub fn parse_with_policy(
+        &self,
+        s: &str,
+        policy: &HashMap<&str, MissingPolicy>,
+    ) -> Result<HashMap<String, Value>, GrokError> {
+        // a match may capture nothing, e.g. only the optional groups, which is still filled
+        let mut map = match self.regex.captures(s) {
+            Some(caps) => self.extract(s, &caps)?,
+            None => return Ok(HashMap::new()),
         };
 
-        for name in names {
-            if let Some(m) = caps.name(name) {
-                let value = m.as_str().to_string();
-                match self.alias.get(name) {
-                    Some((alias, type_)) => {
-                        let value = match type_ {
-                            Some(type_) if type_.eq("int") || type_.eq("long") => Value::Int(
-                                value.parse::<i64>().map_err(|e| format!("{e}: {value}"))?,
-                            ),
-                            Some(type_) if type_.eq("float") || type_.eq("double") => Value::Float(
-                                value.parse::<f64>().map_err(|e| format!("{e}: {value}"))?,
-                            ),
-                            Some(type_) if type_.eq("bool") || type_.eq("boolean") => Value::Bool(
-                                value.parse::<bool>().map_err(|e| format!("{e}: {value}"))?,
-                            ),
-                            _ => Value::String(value),
-                        };
-                        map.insert(alias.clone(), value);
-                    }
-                    None => {
-                        map.insert(name.to_string(), Value::String(value));
-                    }
+        for (field, policy) in policy {
+            if map.contains_key(*field) || !self.keys.values().any(|key| &**key == *field) {
+                continue;
+            }
+            match policy {
+                MissingPolicy::Omit => {}
+                MissingPolicy::Null => {
+                    map.insert(field.to_string(), Value::Null);
+                }
+                MissingPolicy::Default(value) => {
+                    map.insert(field.to_string(), value.clone());
                 }
             }
         }
 
         Ok(map)
     }
-}
 
-#[derive(Default, Debug)]
-pub struct Grok {
-    patterns: HashMap<String, String>,
-}
+    /// parse the input string like [`Pattern::parse`] after removing the ANSI CSI sequences, e.g. the color codes
+    /// of the logs captured from terminals. The captured values refer to the stripped text.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::{Grok, Value};
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("^%{LOGLEVEL:level} %{GREEDYDATA:message}", true).unwrap();
+    /// let result = pattern.parse_stripping_ansi("\x1b[31mERROR\x1b[0m disk full").unwrap();
+    /// assert_eq!(Some(&Value::String("ERROR".into())), result.get("level"));
+    /// ```
+    pub fn parse_stripping_ansi(&self, s: &str) -> Result<HashMap<String, Value>, GrokError> {
+        self.parse(&ANSI_REGEX.replace_all(s, ""))
+    }
 
-impl Grok {
-    /// add a custom pattern, if the pattern is already defined, then it will be overwritten.
+    /// parse the input string, if it can't be matched by this pattern, then parse it by the fallback pattern.
+    ///
     /// # Example
     /// ```
-    /// use grok_rs::Grok;
+    /// use grok_rs::{Grok, Value};
     ///
-    /// let mut grok = Grok::default();
-    /// grok.add_pattern("NAME", r"[A-z0-9._-]+");
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{IP:client} %{WORD:method}", true).unwrap();
+    /// let fallback = grok.compile("%{GREEDYDATA:message}", true).unwrap();
+    /// let result = pattern.parse_or("unexpected line", &fallback).unwrap();
+    /// assert_eq!(Some(&Value::String("unexpected line".into())), result.get("message"));
     /// ```
-    pub fn add_pattern<T: Into<String>>(&mut self, name: T, pattern: T) {
-        self.patterns.insert(name.into(), pattern.into());
+    pub fn parse_or(
+        &self,
+        s: &str,
+        fallback: &Pattern,
+    ) -> Result<HashMap<String, Value>, GrokError> {
+        match self.regex.captures(s) {
+            Some(caps) => self.extract(s, &caps),
+            None => fallback.parse(s),
+        }
     }
 
-    /// Compile the pattern, and return a Pattern.
-    /// - if `named_capture_only` is true, then the unnamed capture group will be ignored.
-    /// - if the pattern is invalid or not found , then an error will be returned.
+    /// parse the input string like [`Pattern::parse`], and return the fields sorted by name,
+    /// which is stable for the human-readable output and the golden tests.
     ///
-    /// Due to the compile process is heavy, it's recommended compile the pattern once and reuse it.
+    /// # Example
+    /// ```
+    /// use grok_rs::{Grok, Value};
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{WORD:b} %{WORD:a}", true).unwrap();
+    /// let result = pattern.parse_sorted("x y").unwrap();
+    /// assert_eq!(vec![
+    ///     ("a".to_string(), Value::String("y".into())),
+    ///     ("b".to_string(), Value::String("x".into())),
+    /// ], result);
+    /// ```
+    pub fn parse_sorted(&self, s: &str) -> Result<Vec<(String, Value)>, GrokError> {
+        let mut fields = self.parse(s)?.into_iter().collect::<Vec<_>>();
+        fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(fields)
+    }
+
+    /// validate that the whole input string conforms to the pattern, and every typed field can be converted.
+    ///  - if the input can't be matched at all, then `NoMatch` will be returned.
+    ///  - if only a part of the input can be matched, then `PartialMatch` with the matched range will be returned.
+    ///  - if any value can't be converted to the specified type, then the first conversion error will be returned.
     ///
     /// # Example
+    /// ```
+    /// use grok_rs::{Grok, GrokError};
     ///
-    /// the USERNAME will be ignored because `named_capture_only` is true.
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{WORD:method} %{INT:status:int}", true).unwrap();
+    /// assert_eq!(Ok(()), pattern.validate("GET 200"));
+    /// assert_eq!(Err(GrokError::PartialMatch { start: 0, end: 7 }), pattern.validate("GET 200 garbage"));
+    /// ```
+    pub fn validate(&self, s: &str) -> Result<(), GrokError> {
+        let anchored = self
+            .anchored
+            .get_or_init(|| {
+                Regex::new(&format!("^(?:{})$", self.regex.as_str()))
+                    .map_err(|e| GrokError::Regex(e.to_string()))
+            })
+            .as_ref()
+            .map_err(Clone::clone)?;
+
+        match anchored.captures(s) {
+            Some(caps) => self.extract(s, &caps).map(|_| ()),
+            None => match self.regex.find(s) {
+                Some(m) => Err(GrokError::PartialMatch {
+                    start: m.start(),
+                    end: m.end(),
+                }),
+                None => Err(GrokError::NoMatch),
+            },
+        }
+    }
+
+    /// parse the input string like [`Pattern::parse`], but the keys of the result are interned,
+    /// i.e. the same field shares the same `Arc<str>` across parses, which reduces the allocations
+    /// when parsing lots of records.
     ///
+    /// # Example
     /// ```
+    /// use std::sync::Arc;
     /// use grok_rs::Grok;
+    ///
     /// let grok = Grok::default();
-    /// let pattern = grok.compile("%{USERNAME} %{EMAILADDRESS:email}", true).unwrap();
+    /// let pattern = grok.compile("%{WORD:method}", true).unwrap();
+    /// let a = pattern.parse_interned("GET").unwrap();
+    /// let b = pattern.parse_interned("POST").unwrap();
+    /// let (a, b) = (a.keys().next().unwrap(), b.keys().next().unwrap());
+    /// assert!(Arc::ptr_eq(a, b));
     /// ```
-    pub fn compile(&self, s: &str, named_capture_only: bool) -> Result<Pattern, String> {
-        let mut alias_map = HashMap::new();
-        let mut haystack = s.to_string();
-        let mut index = 0;
-        let mut iter_left = MAX_RECURSION;
-
-        while let Some(caps) = GROK_REGEX.captures(haystack.clone().as_str()) {
-            if iter_left <= 0 {
-                return Err(format!("max recursion {MAX_RECURSION} reached"));
-            }
-            iter_left -= 1;
+    pub fn parse_interned(&self, s: &str) -> Result<HashMap<Arc<str>, Value>, GrokError> {
+        match self.regex.captures(s) {
+            Some(caps) => self.extract_by(s, &caps, Arc::clone),
+            None => Ok(HashMap::new()),
+        }
+    }
 
-            let name = caps.get(NAME_INDEX).ok_or("name not found")?.as_str();
-            let pattern = caps.get(PATTERN_INDEX).ok_or("pattern not found")?.as_str();
+    /// parse the input string like [`Pattern::parse_interned`], but into the map of the context,
+    /// which is cleared and reused by every call, so its allocation is kept across parses.
+    ///
+    /// The returned map borrows the context, so it must be dropped, e.g. after copying out the values,
+    /// before the context is used again. If an error is returned, the map of the context is left
+    /// in an unspecified state until the next call clears it.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::{Grok, ParseContext, Value};
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{WORD:method}", true).unwrap();
+    /// let mut ctx = ParseContext::default();
+    /// let mut methods = Vec::new();
+    /// for line in ["GET", "POST"] {
+    ///     let result = pattern.parse_ctx(&mut ctx, line).unwrap();
+    ///     methods.push(result["method"].clone());
+    /// }
+    /// assert_eq!(vec![Value::String("GET".into()), Value::String("POST".into())], methods);
+    /// ```
+    pub fn parse_ctx<'c>(
+        &self,
+        ctx: &'c mut ParseContext,
+        s: &str,
+    ) -> Result<&'c HashMap<Arc<str>, Value>, GrokError> {
+        ctx.map.clear();
+        if let Some(caps) = self.regex.captures(s) {
+            self.extract_into(s, &caps, &mut ctx.map, Arc::clone)?;
+        }
+        Ok(&ctx.map)
+    }
 
-            let pattern_regex = self
-                .patterns
-                .get(pattern)
-                .or(DEFAULT_PATTERNS.get(pattern))
-                .ok_or(format!("pattern: {pattern}  not found"))?;
+    /// the number of the capture groups which are never read by [`Pattern::parse`], i.e. the unnamed groups,
+    /// e.g. the inner groups of the nested patterns like `IPV6`. They still cost when matching,
+    /// so a large number hints that the pattern can be tuned, e.g. by `named_capture_only`
+    /// or by rewriting the groups as non-capturing `(?:...)`.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// assert_eq!(0, grok.compile("%{WORD:word}", true).unwrap().unused_groups());
+    /// assert!(grok.compile("%{IPV6:ip}", true).unwrap().unused_groups() > 0);
+    /// ```
+    pub fn unused_groups(&self) -> usize {
+        let named = self.regex.capture_names().flatten().count();
+        // the implicit group 0 of the whole match is not counted
+        self.regex.captures_len() - 1 - named
+    }
 
-            let to_replace = format!("%{{{name}}}");
+    /// estimate the byte offset of the input where the match breaks down, e.g. to debug a near-miss input.
+    /// The offset is the furthest position the pattern can reach from any start position of the input,
+    /// so it's approximate, e.g. an optional part of the pattern may stop earlier than expected.
+    ///  - if the input is matched, then `None` will be returned.
+    ///  - the non-ASCII input stops the estimation if the pattern has Unicode word boundaries, e.g. `\b`.
+    ///
+    /// It runs the pattern from every start position, so it's too slow for the hot path.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{IP:client} %{INT:status}", true).unwrap();
+    /// assert_eq!(Some(9), pattern.match_failure("10.0.0.1 OK"));
+    /// assert_eq!(None, pattern.match_failure("10.0.0.1 200"));
+    /// ```
+    pub fn match_failure(&self, s: &str) -> Option<usize> {
+        use regex_automata::{hybrid::dfa::DFA, util::start, Anchored};
 
-            while haystack.matches(&to_replace).count() > 0 {
-                let replacement = match caps.get(ALIAS_INDEX) {
-                    None if named_capture_only => {
-                        format!("(?:{pattern_regex})")
-                    }
-                    _ => {
-                        let new_name = format!("name{index}");
-                        let origin_alias =
-                            caps.get(ALIAS_INDEX).map(|m| m.as_str()).unwrap_or(pattern);
-                        let type_ = caps.get(TYPE_INDEX).map(|m| m.as_str().to_string());
-                        alias_map.insert(new_name.clone(), (origin_alias.to_string(), type_));
-                        format!("(?<{new_name}>{pattern_regex})")
-                    }
-                };
+        if self.regex.is_match(s) {
+            return None;
+        }
 
-                haystack = haystack.replacen(&to_replace, &replacement, 1);
-                index += 1;
+        let dfa = DFA::builder()
+            .configure(DFA::config().unicode_word_boundary(true))
+            .build(self.regex.as_str())
+            .ok()?;
+        let mut cache = dfa.create_cache();
+        let bytes = s.as_bytes();
+        let mut furthest = 0;
+
+        for start in (0..=s.len()).filter(|i| s.is_char_boundary(*i)) {
+            if start < furthest {
+                continue;
+            }
+            let config = start::Config::new()
+                .anchored(Anchored::Yes)
+                .look_behind(start.checked_sub(1).map(|i| bytes[i]));
+            let Ok(mut state) = dfa.start_state(&mut cache, &config) else {
+                continue;
+            };
+
+            let mut end = start;
+            for &b in &bytes[start..] {
+                match dfa.next_state(&mut cache, state, b) {
+                    Ok(next) if !next.is_dead() && !next.is_quit() => state = next,
+                    _ => break,
+                }
+                end += 1;
+            }
+            // the start which can't consume any byte doesn't count, e.g. the end of the input
+            if end > start {
+                furthest = furthest.max(end);
             }
         }
 
-        let re = Regex::new(haystack.as_str()).map_err(|e| e.to_string())?;
-        Ok(Pattern::new(re, alias_map))
+        Some(furthest)
     }
-}
 
-impl<T: Into<String>> FromIterator<(T, T)> for Grok {
-    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
-        let mut grok = Grok::default();
-        for (k, v) in iter {
-            grok.add_pattern(k, v);
-        }
-        grok
+    /// count the non-overlapping matches of the pattern in the input string, without extracting the captures.
+    /// - the empty matches are not counted, e.g. `%{DATA}` matches an empty string everywhere.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{IPV4}", false).unwrap();
+    /// assert_eq!(2, pattern.match_count("10.0.0.1 -> 10.0.0.2"));
+    /// ```
+    pub fn match_count(&self, s: &str) -> usize {
+        self.regex.find_iter(s).filter(|m| !m.is_empty()).count()
     }
-}
 
-impl<S: Into<String>, const N: usize> From<[(S, S); N]> for Grok {
-    fn from(arr: [(S, S); N]) -> Self {
-        Self::from_iter(arr)
+    /// split the input string into records by the separator, and parse each record.
+    /// - the empty records, e.g. after a trailing separator, are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{WORD:method} %{INT:status:int}", true).unwrap();
+    /// let results = pattern.parse_records("GET 200;POST 201;", ";").collect::<Vec<_>>();
+    /// assert_eq!(2, results.len());
+    /// ```
+    pub fn parse_records<'a>(
+        &'a self,
+        s: &'a str,
+        sep: &'a str,
+    ) -> impl Iterator<Item = Result<HashMap<String, Value>, GrokError>> + 'a {
+        s.split(sep)
+            .filter(|record| !record.is_empty())
+            .map(|record| self.parse(record))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// parse the input string, and return the values of the two fields positionally without building a map.
+    /// - if the input can't be matched, or any of the fields is not captured, then `None` will be returned.
+    /// - the original input and the default values are returned like [`Pattern::parse`].
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::{Grok, Value};
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{IP:ip}:%{INT:port:int}", true).unwrap();
+    /// let result = pattern.parse_tuple2("127.0.0.1:8080", "ip", "port").unwrap();
+    /// assert_eq!(Some((Value::String("127.0.0.1".into()), Value::Int(8080))), result);
+    /// ```
+    pub fn parse_tuple2(
+        &self,
+        s: &str,
+        a: &str,
+        b: &str,
+    ) -> Result<Option<(Value, Value)>, GrokError> {
+        let caps = match self.regex.captures(s) {
+            Some(caps) => caps,
+            None => return Ok(None),
+        };
 
-    struct Case<'a> {
-        patterns: Vec<(&'a str, &'a str)>,
-        pattern: &'a str,
-        input: &'a str,
-        expected: HashMap<String, Value>,
-        named_capture_only: bool,
+        match (self.field(s, &caps, a)?, self.field(s, &caps, b)?) {
+            (Some(a), Some(b)) => Ok(Some((a, b))),
+            _ => Ok(None),
+        }
     }
 
-    fn assert(c: Case<'_>) {
-        let grok = Grok::from_iter(c.patterns);
-        let pattern = grok.compile(c.pattern, c.named_capture_only).unwrap();
-        assert_eq!(c.expected, pattern.parse(c.input).unwrap());
+    /// parse the input string, and return the values of the three fields positionally without building a map.
+    /// - if the input can't be matched, or any of the fields is not captured, then `None` will be returned.
+    pub fn parse_tuple3(
+        &self,
+        s: &str,
+        a: &str,
+        b: &str,
+        c: &str,
+    ) -> Result<Option<(Value, Value, Value)>, GrokError> {
+        let caps = match self.regex.captures(s) {
+            Some(caps) => caps,
+            None => return Ok(None),
+        };
+
+        match (
+            self.field(s, &caps, a)?,
+            self.field(s, &caps, b)?,
+            self.field(s, &caps, c)?,
+        ) {
+            (Some(a), Some(b), Some(c)) => Ok(Some((a, b, c))),
+            _ => Ok(None),
+        }
     }
 
-    fn asserts(cases: Vec<Case<'_>>) {
-        for c in cases {
-            assert(c);
+    /// parse the input string, and build a typed value by [`FromGrok`].
+    /// - if the input can't be matched, then `None` will be returned.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use grok_rs::{FromGrok, FromValue, Grok, GrokError, Value};
+    ///
+    /// struct Request {
+    ///     method: String,
+    ///     status: u16,
+    /// }
+    ///
+    /// impl FromGrok for Request {
+    ///     fn from_grok(map: &HashMap<String, Value>) -> Result<Self, GrokError> {
+    ///         Ok(Self {
+    ///             method: FromValue::from_field(map, "method")?,
+    ///             status: FromValue::from_field(map, "status")?,
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{WORD:method} %{INT:status}", true).unwrap();
+    /// let request = pattern.parse_into::<Request>("GET 200").unwrap().unwrap();
+    /// assert_eq!(("GET", 200), (request.method.as_str(), request.status));
+    /// ```
+    pub fn parse_into<T: FromGrok>(&self, s: &str) -> Result<Option<T>, GrokError> {
+        match self.regex.captures(s) {
+            Some(caps) => T::from_grok(&self.extract(s, &caps)?).map(Some),
+            None => Ok(None),
         }
     }
 
-    #[test]
-    fn test_simple_add_pattern() {
-        let mut grok = Grok::default();
-        grok.add_pattern("NAME", r"[A-z0-9._-]+");
-        let pattern = grok.compile("%{NAME}", false).unwrap();
-        let expected: HashMap<String, Value> = [("NAME", "admin")]
-            .into_iter()
-            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
-            .collect();
+    fn extract(&self, s: &str, caps: &Captures<'_>) -> Result<HashMap<String, Value>, GrokError> {
+        self.extract_by(s, caps, |key| key.to_string())
+    }
 
-        assert_eq!(expected, pattern.parse("admin").unwrap());
-        assert_eq!(expected, pattern.parse("admin user").unwrap());
+    /// extract the captured values, the keys of the result are built from the interned keys.
+    fn extract_by<K: Eq + Hash>(
+        &self,
+        s: &str,
+        caps: &Captures<'_>,
+        to_key: impl Fn(&Arc<str>) -> K,
+    ) -> Result<HashMap<K, Value>, GrokError> {
+        let mut map = HashMap::new();
+        self.extract_into(s, caps, &mut map, to_key)?;
+        Ok(map)
+    }
+
+    /// extract the captured values into the given map, the existing entries are kept.
+    fn extract_into<K: Eq + Hash>(
+        &self,
+        s: &str,
+        caps: &Captures<'_>,
+        map: &mut HashMap<K, Value>,
+        to_key: impl Fn(&Arc<str>) -> K,
+    ) -> Result<(), GrokError> {
+        for name in self.regex.capture_names().flatten() {
+            if self.is_map_group(name) {
+                continue;
+            }
+            if let Some(m) = caps.name(name) {
+                let (_, value) = self.rename(name, m.as_str())?;
+                let key = &self.keys[name];
+
+                match map.entry(to_key(key)) {
+                    Entry::Occupied(mut e) if self.collect_arrays => {
+                        self.push_array(key, e.get_mut(), value)?
+                    }
+                    Entry::Occupied(mut e) => {
+                        e.insert(value);
+                    }
+                    Entry::Vacant(e) => {
+                        e.insert(value);
+                    }
+                }
+            }
+        }
+
+        for group in &self.maps {
+            if let Some(value) = group.extract(s, caps, self.max_array_len, self.array_overflow)? {
+                map.insert(to_key(&group.field), value);
+            }
+        }
+
+        if let Some(field) = &self.original_field {
+            map.entry(to_key(field))
+                .or_insert_with(|| Value::String(s.to_string()));
+        }
+
+        for (field, value) in &self.defaults {
+            map.entry(to_key(field)).or_insert_with(|| value.clone());
+        }
+
+        Ok(())
+    }
+
+    /// the capture group is a part of a [`Value::Map`], so it's not a field of the result.
+    fn is_map_group(&self, name: &str) -> bool {
+        self.maps.iter().any(|group| group.owns(name))
+    }
+
+    /// push the value of the field captured again into the array, with the limit of `max_array_len`.
+    fn push_array(&self, field: &str, slot: &mut Value, value: Value) -> Result<(), GrokError> {
+        let mut values = match std::mem::replace(slot, Value::Array(Vec::new())) {
+            Value::Array(values) => values,
+            single => vec![single],
+        };
+
+        match self.max_array_len {
+            Some(max) if values.len() >= max => {
+                if self.array_overflow == ArrayOverflow::Error {
+                    return Err(GrokError::ArrayTooLong {
+                        field: field.to_string(),
+                        max,
+                    });
+                }
+            }
+            _ => values.push(value),
+        }
+
+        *slot = Value::Array(values);
+        Ok(())
+    }
+
+    /// the value of the field like [`Pattern::parse`], including the [`Value::Map`], the original input
+    /// and the default value, but if the field is captured by several groups, then the last one wins
+    /// even with [`CompileOptions::collect_arrays`].
+    fn field(&self, s: &str, caps: &Captures<'_>, field: &str) -> Result<Option<Value>, GrokError> {
+        let mut value = None;
+
+        for name in self.regex.capture_names().flatten() {
+            if self.is_map_group(name) {
+                continue;
+            }
+            if let Some(m) = caps.name(name) {
+                if *self.keys[name] == *field {
+                    value = Some(self.rename(name, m.as_str())?.1);
+                }
+            }
+        }
+
+        for group in self.maps.iter().filter(|group| *group.field == *field) {
+            if let Some(map) = group.extract(s, caps, self.max_array_len, self.array_overflow)? {
+                value = Some(map);
+            }
+        }
+        if value.is_none() && self.original_field.as_deref() == Some(field) {
+            value = Some(Value::String(s.to_string()));
+        }
+        if value.is_none() {
+            value = self.defaults.get(field).cloned();
+        }
+
+        Ok(value)
+    }
+
+    /// rename the captured group based on alias, and convert the value to the specified type.
+    fn rename<'a>(&'a self, name: &'a str, value: &str) -> Result<(&'a str, Value), GrokError> {
+        match self.alias.get(name) {
+            Some((alias, type_)) => match convert(value, type_.as_deref()) {
+                Ok(value) => Ok((alias, value)),
+                Err(_) if self.inferred.contains(name) => {
+                    Ok((alias, Value::String(value.to_string())))
+                }
+                Err(e) => Err(e),
+            },
+            None => Ok((name, Value::String(value.to_string()))),
+        }
+    }
+
+    /// describe the fields produced by [`Pattern::parse`], in the order of the capture groups.
+    /// - a field captured by several groups is listed once, with the type of its first group.
+    /// - the fields of [`Value::Map`] are listed after the captured ones.
+    /// - the field of [`CompileOptions::keep_original_field`] is listed after them if it's not captured,
+    ///   then the fields of [`CompileOptions::defaults`] which are not captured, sorted by name.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{NUMBER:bytes:int} %{WORD:method}", true).unwrap();
+    /// let names = pattern.schema().into_iter().map(|f| f.name).collect::<Vec<_>>();
+    /// assert_eq!(vec!["bytes", "method"], names);
+    /// ```
+    pub fn schema(&self) -> Vec<FieldSchema> {
+        let mut fields: Vec<FieldSchema> = Vec::new();
+
+        for name in self.regex.capture_names().flatten() {
+            let key = &self.keys[name];
+            if self.is_map_group(name) || fields.iter().any(|f| *f.name == **key) {
+                continue;
+            }
+            let type_ = self.alias.get(name).and_then(|(_, type_)| type_.clone());
+            fields.push(FieldSchema {
+                name: key.to_string(),
+                type_,
+                description: self.descriptions.get(&**key).cloned(),
+            });
+        }
+
+        for group in &self.maps {
+            if !fields.iter().any(|f| *f.name == *group.field) {
+                fields.push(FieldSchema {
+                    name: group.field.to_string(),
+                    type_: None,
+                    description: None,
+                });
+            }
+        }
+
+        if let Some(original) = &self.original_field {
+            if !fields.iter().any(|f| *f.name == **original) {
+                fields.push(FieldSchema {
+                    name: original.to_string(),
+                    type_: None,
+                    description: None,
+                });
+            }
+        }
+
+        let mut defaults = self
+            .defaults
+            .keys()
+            .filter(|field| !fields.iter().any(|f| *f.name == ***field))
+            .collect::<Vec<_>>();
+        defaults.sort();
+        fields.extend(defaults.into_iter().map(|field| FieldSchema {
+            name: field.to_string(),
+            type_: None,
+            description: None,
+        }));
+
+        fields
+    }
+
+    /// the human readable warnings about the pattern, e.g. a field captured by several groups.
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        let mut groups: Vec<(&str, Vec<Option<&str>>)> = Vec::new();
+        for name in self.regex.capture_names().flatten() {
+            if self.is_map_group(name) {
+                continue;
+            }
+            let key = &*self.keys[name];
+            let type_ = self.alias.get(name).and_then(|(_, type_)| type_.as_deref());
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, types)) => types.push(type_),
+                None => groups.push((key, vec![type_])),
+            }
+        }
+
+        for (key, types) in groups {
+            if types.len() > 1 && !self.collect_arrays {
+                warnings.push(format!(
+                    "field `{key}` is captured by {} groups, the last captured value wins",
+                    types.len()
+                ));
+            }
+            if types.iter().any(|t| *t != types[0]) {
+                warnings.push(format!("field `{key}` is captured with different types"));
+            }
+        }
+
+        let unused = self.unused_groups();
+        if unused > 0 {
+            warnings.push(format!(
+                "{unused} unnamed capture groups are never extracted"
+            ));
+        }
+
+        warnings
+    }
+}
+
+/// parse the input string by the first pattern, then parse the value of `feed_field` by the next pattern,
+/// and so on, e.g. the outer pattern extracts the message, and the inner one decomposes it.
+///  - the results are merged, the later pattern wins for the same field, including `feed_field` itself.
+///  - the chain stops when `feed_field` is not captured as a string, or the next pattern doesn't match.
+///  - if the first pattern doesn't match, then an empty map will be returned.
+///
+/// # Example
+/// ```
+/// use grok_rs::{parse_chain, Grok, Value};
+///
+/// let grok = Grok::default();
+/// let outer = grok.compile("%{LOGLEVEL:level} %{GREEDYDATA:msg}", true).unwrap();
+/// let inner = grok.compile("user=%{USERNAME:user}", true).unwrap();
+/// let result = parse_chain(&[&outer, &inner], "INFO user=bob", "msg").unwrap();
+/// assert_eq!(Some(&Value::String("bob".into())), result.get("user"));
+/// ```
+pub fn parse_chain(
+    patterns: &[&Pattern],
+    s: &str,
+    feed_field: &str,
+) -> Result<HashMap<String, Value>, GrokError> {
+    let Some((first, rest)) = patterns.split_first() else {
+        return Ok(HashMap::new());
+    };
+
+    let mut merged = first.parse(s)?;
+    for pattern in rest {
+        let result = match merged.get(feed_field) {
+            Some(Value::String(feed)) => pattern.parse(feed)?,
+            _ => break,
+        };
+        if result.is_empty() {
+            break;
+        }
+        merged.extend(result);
+    }
+
+    Ok(merged)
+}
+
+/// The reusable buffers of [`Pattern::parse_ctx`], it can be shared by different patterns.
+#[derive(Debug, Default)]
+pub struct ParseContext {
+    map: HashMap<Arc<str>, Value>,
+}
+
+impl ParseContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// The description of a field produced by a [`Pattern`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSchema {
+    /// the key of the field in the parsed map, i.e. the alias or the generated group name.
+    pub name: String,
+    /// the type the value is converted into, `None` means it's kept as a string.
+    pub type_: Option<String>,
+    /// the description of the placeholder, e.g. `%{NUMBER:bytes:int:"response size"}`.
+    pub description: Option<String>,
+}
+
+/// The rough cost of a pattern, see [`Grok::complexity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Complexity {
+    /// the length of the expanded regex in bytes.
+    pub regex_len: usize,
+    /// the number of the capture groups, both named and unnamed.
+    pub capture_groups: usize,
+    /// the number of `|` in the expanded regex.
+    pub alternations: usize,
+}
+
+/// The compiled [`Pattern`] with a description of what it produces, see [`Grok::compile_detailed`].
+#[derive(Debug)]
+pub struct CompileResult {
+    pub pattern: Pattern,
+    /// the same as [`Pattern::schema`].
+    pub fields: Vec<FieldSchema>,
+    pub warnings: Vec<String>,
+    /// the number of the expansion rounds, every round replaces all the occurrences of a placeholder,
+    /// e.g. `%{WORD:a}`, including the ones of the nested definitions. The compile fails with [`GrokError::RecursionLimit`] if it exceeds the limit.
+    pub iterations: usize,
+}
+
+/// Options used to compile an expression into a [`Pattern`].
+///
+/// The options can be built once and reused across many [`Grok::compile_with_opts`] calls,
+/// `CompileOptions::default()` behaves the same as `grok.compile(s, false)`.
+///
+/// # Example
+/// ```
+/// use grok_rs::{CompileOptions, Grok};
+///
+/// let grok = Grok::default();
+/// let opts = CompileOptions::default()
+///     .case_insensitive(true)
+///     .unicode(false);
+/// let pattern = grok.compile_with_opts("%{WORD:word}", &opts).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+    /// if true, then the unnamed capture group will be ignored.
+    pub named_capture_only: bool,
+    /// if true, then letters match both upper and lower case.
+    pub case_insensitive: bool,
+    /// if false, then character classes like `\w` only match ASCII.
+    /// Patterns which can match invalid UTF-8 (e.g. `.` or negated classes) are rejected in this mode.
+    pub unicode: bool,
+    /// if true, then `.` matches `\n` as well.
+    pub dot_matches_new_line: bool,
+    /// if set, then the whole input is kept in this field of the result when the input is matched.
+    /// the captured value takes precedence if the pattern captures the same field.
+    pub keep_original_field: Option<String>,
+    /// if true, then every placeholder of the expression only matches a whole token, i.e. it's not
+    /// preceded or followed by a word character, e.g. `%{MAC}` won't match inside a longer hex blob.
+    ///
+    /// Only the placeholders written in the expression are wrapped, not the nested ones of their definitions.
+    /// The half word boundaries `\b{start-half}` and `\b{end-half}` are used instead of `\b`, so the
+    /// patterns starting or ending with a non-word character, e.g. `QUOTEDSTRING`, still match;
+    /// and the patterns which already have `\b`, e.g. `WORD` or `IPV4`, behave the same.
+    pub exact: bool,
+    /// if true, then `GREEDYDATA` is expanded to the non-greedy `.*?` instead of `.*`, so it no longer
+    /// swallows the fields that should be matched by the later patterns. It's only applied when
+    /// `GREEDYDATA` keeps the default definition `.*`.
+    ///
+    /// Notice a non-greedy `GREEDYDATA` at the end of the expression matches an empty string
+    /// unless the expression is anchored, e.g. by `$`.
+    pub lazy_greedydata: bool,
+    /// if true, then the values of a field captured several times, e.g. `%{WORD:tag} %{WORD:tag}`,
+    /// are collected into a [`Value::Array`] in order. Otherwise the last captured value wins.
+    pub collect_arrays: bool,
+    /// the max number of the values collected into a [`Value::Array`], or the pairs into a [`Value::Map`],
+    /// unlimited by default.
+    /// It must be at least 1, since the field captured only once is kept as a single value, not an array,
+    /// `InvalidOption` is returned when compiling otherwise.
+    pub max_array_len: Option<usize>,
+    /// what to do when more values than `max_array_len` are captured, an error by default.
+    pub array_overflow: ArrayOverflow,
+    /// the values of the fields which are not captured when the input is matched,
+    /// e.g. the fields of the branch not taken by an alternation like `%{IP:ip}|%{HOSTNAME:host}`.
+    pub defaults: HashMap<String, Value>,
+    /// if true, then the type of the aliased placeholder without a type is inferred from the alias
+    /// by `type_inference`, e.g. `%{NUMBER:destination.port}` is converted to int.
+    /// The explicit type, e.g. `%{NUMBER:destination.port:float}`, and the default type of the pattern
+    /// take precedence. Unlike the explicit type, the captured value which can't be converted to
+    /// the inferred type is kept as string, e.g. `%{IPORHOST:source.ip}` of a hostname,
+    /// except for the pattern compiled from the cached [`Grok::expand`] by [`Pattern::compile_expanded`].
+    pub infer_types_by_name: bool,
+    /// the suffixes of the alias and the inferred types, the first matching suffix wins.
+    /// By default:
+    ///
+    /// | suffix               | type |
+    /// |----------------------|------|
+    /// | `.port`, `_port`     | int  |
+    /// | `.bytes`, `_bytes`   | long |
+    /// | `.ip`, `_ip`         | ip   |
+    ///
+    /// There is no type for the timestamps like `.ts`, since `date` only reads the numeric dates of `DATE`,
+    /// not the timestamps of e.g. `TIMESTAMP_ISO8601`.
+    pub type_inference: Vec<(String, String)>,
+}
+
+/// What to do when a field is captured more times than [`CompileOptions::max_array_len`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArrayOverflow {
+    /// return [`GrokError::ArrayTooLong`].
+    #[default]
+    Error,
+    /// keep the first `max_array_len` values, and drop the others.
+    Truncate,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            named_capture_only: false,
+            case_insensitive: false,
+            unicode: true,
+            dot_matches_new_line: false,
+            keep_original_field: None,
+            exact: false,
+            lazy_greedydata: false,
+            collect_arrays: false,
+            max_array_len: None,
+            array_overflow: ArrayOverflow::Error,
+            defaults: HashMap::new(),
+            infer_types_by_name: false,
+            type_inference: [
+                (".port", "int"),
+                ("_port", "int"),
+                (".bytes", "long"),
+                ("_bytes", "long"),
+                (".ip", "ip"),
+                ("_ip", "ip"),
+            ]
+            .into_iter()
+            .map(|(suffix, type_)| (suffix.to_string(), type_.to_string()))
+            .collect(),
+        }
+    }
+}
+
+impl CompileOptions {
+    pub fn named_capture_only(mut self, yes: bool) -> Self {
+        self.named_capture_only = yes;
+        self
+    }
+
+    pub fn case_insensitive(mut self, yes: bool) -> Self {
+        self.case_insensitive = yes;
+        self
+    }
+
+    pub fn unicode(mut self, yes: bool) -> Self {
+        self.unicode = yes;
+        self
+    }
+
+    pub fn dot_matches_new_line(mut self, yes: bool) -> Self {
+        self.dot_matches_new_line = yes;
+        self
+    }
+
+    pub fn exact(mut self, yes: bool) -> Self {
+        self.exact = yes;
+        self
+    }
+
+    pub fn lazy_greedydata(mut self, yes: bool) -> Self {
+        self.lazy_greedydata = yes;
+        self
+    }
+
+    pub fn collect_arrays(mut self, yes: bool) -> Self {
+        self.collect_arrays = yes;
+        self
+    }
+
+    pub fn max_array_len(mut self, max: usize, overflow: ArrayOverflow) -> Self {
+        self.max_array_len = Some(max);
+        self.array_overflow = overflow;
+        self
+    }
+
+    pub fn keep_original_field<T: Into<String>>(mut self, field: T) -> Self {
+        self.keep_original_field = Some(field.into());
+        self
+    }
+
+    pub fn default_value<T: Into<String>>(mut self, field: T, value: Value) -> Self {
+        self.defaults.insert(field.into(), value);
+        self
+    }
+
+    pub fn infer_types_by_name(mut self, yes: bool) -> Self {
+        self.infer_types_by_name = yes;
+        self
+    }
+
+    /// infer the type of the alias ending with the suffix, it takes precedence over the existing suffixes.
+    pub fn infer_type<T: Into<String>>(mut self, suffix: T, type_: T) -> Self {
+        self.type_inference.insert(0, (suffix.into(), type_.into()));
+        self
+    }
+
+    /// the inferred type of the alias by `type_inference`.
+    fn inferred_type(&self, alias: &str) -> Option<&str> {
+        if !self.infer_types_by_name {
+            return None;
+        }
+        self.type_inference
+            .iter()
+            .find(|(suffix, _)| alias.ends_with(suffix.as_str()))
+            .map(|(_, type_)| type_.as_str())
+    }
+
+    /// the inline flags prepended to the expanded regex, e.g. `(?is-u)`.
+    fn flags(&self) -> String {
+        let mut on = String::new();
+        if self.case_insensitive {
+            on.push('i');
+        }
+        if self.dot_matches_new_line {
+            on.push('s');
+        }
+        let off = if self.unicode { "" } else { "-u" };
+
+        if on.is_empty() && off.is_empty() {
+            String::new()
+        } else {
+            format!("(?{on}{off})")
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Grok {
+    patterns: HashMap<String, String>,
+    /// the alias of the type to its built-in target type.
+    type_aliases: HashMap<String, String>,
+    /// the default type of the pattern, applied if the placeholder doesn't specify a type.
+    pattern_types: HashMap<String, String>,
+}
+
+impl Grok {
+    /// add a custom pattern, if the pattern is already defined, then it will be overwritten.
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let mut grok = Grok::default();
+    /// grok.add_pattern("NAME", r"[A-z0-9._-]+");
+    /// ```
+    pub fn add_pattern<T: Into<String>>(&mut self, name: T, pattern: T) {
+        self.patterns.insert(name.into(), pattern.into());
+    }
+
+    /// Create a Grok with the patterns loaded from the source, the default patterns are still available.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::{Grok, ReaderSource};
+    ///
+    /// let source = ReaderSource::new("NAME [a-z]+\nGREETING hello|hi".as_bytes());
+    /// let grok = Grok::from_source(source).unwrap();
+    /// assert!(grok.compile("%{GREETING} %{NAME}", false).is_ok());
+    /// ```
+    pub fn from_source<S: PatternSource>(src: S) -> Result<Self, GrokError> {
+        Ok(Self::from_iter(src.patterns()?))
+    }
+
+    /// register an alias of a built-in type, then the alias is converted exactly like the target type.
+    /// if the alias is already registered, then it will be overwritten.
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use grok_rs::{Grok, Value};
+    ///
+    /// let mut grok = Grok::default();
+    /// grok.register_type_alias("integer", "int");
+    /// let pattern = grok.compile("%{NUMBER:n:integer}", true).unwrap();
+    /// let expected = HashMap::from([("n".to_string(), Value::Int(42))]);
+    /// assert_eq!(expected, pattern.parse("42").unwrap());
+    /// ```
+    pub fn register_type_alias<T: Into<String>>(&mut self, alias: T, target: T) {
+        self.type_aliases.insert(alias.into(), target.into());
+    }
+
+    /// resolve the type alias to the built-in type.
+    fn resolve_type(&self, type_: &str) -> Result<String, GrokError> {
+        let target = self
+            .type_aliases
+            .get(type_)
+            .map_or(type_, |target| target.as_str());
+
+        if convert::is_builtin_type(target) {
+            Ok(target.to_string())
+        } else {
+            Err(GrokError::UnknownType(type_.to_string()))
+        }
+    }
+
+    /// return the definition of the pattern, the user-defined patterns take precedence over the default ones.
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let mut grok = Grok::default();
+    /// grok.add_pattern("NAME", r"[A-z0-9._-]+");
+    /// assert_eq!(Some(r"[A-z0-9._-]+"), grok.pattern_definition("NAME"));
+    /// assert_eq!(None, grok.pattern_definition("NOT_EXIST"));
+    /// ```
+    pub fn pattern_definition(&self, name: &str) -> Option<&str> {
+        self.patterns
+            .get(name)
+            .or(DEFAULT_PATTERNS.get(name))
+            .map(|s| s.as_str())
+    }
+
+    /// check that every pattern is defined, either user-defined or a default pattern,
+    /// e.g. to fail fast at startup. The missing ones are listed in the error, separated by `, `.
+    /// # Example
+    /// ```
+    /// use grok_rs::{Grok, GrokError};
+    ///
+    /// let grok = Grok::default();
+    /// assert_eq!(Ok(()), grok.require(&["IP", "USERNAME"]));
+    /// assert_eq!(
+    ///     Err(GrokError::PatternNotFound("NOPE".to_string())),
+    ///     grok.require(&["IP", "NOPE"])
+    /// );
+    /// ```
+    pub fn require(&self, names: &[&str]) -> Result<(), GrokError> {
+        let missing = names
+            .iter()
+            .filter(|name| self.pattern_definition(name).is_none())
+            .copied()
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(GrokError::PatternNotFound(missing.join(", ")))
+        }
+    }
+
+    /// Compile the pattern, and return a Pattern.
+    /// - if `named_capture_only` is true, then the unnamed capture group will be ignored.
+    /// - if the pattern is invalid or not found , then an error will be returned.
+    ///
+    /// Due to the compile process is heavy, it's recommended compile the pattern once and reuse it.
+    ///
+    /// # Example
+    ///
+    /// the USERNAME will be ignored because `named_capture_only` is true.
+    ///
+    /// ```
+    /// use grok_rs::Grok;
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{USERNAME} %{EMAILADDRESS:email}", true).unwrap();
+    /// ```
+    pub fn compile(&self, s: &str, named_capture_only: bool) -> Result<Pattern, GrokError> {
+        let opts = CompileOptions::default().named_capture_only(named_capture_only);
+        self.compile_with_opts(s, &opts)
+    }
+
+    /// Compile the pattern with the given options, and return a Pattern.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use grok_rs::{CompileOptions, Grok};
+    ///
+    /// let grok = Grok::default();
+    /// let opts = CompileOptions::default().case_insensitive(true);
+    /// let pattern = grok.compile_with_opts("GET %{URIPATH:path}", &opts).unwrap();
+    /// assert!(!pattern.parse("get /index.html").unwrap().is_empty());
+    /// ```
+    pub fn compile_with_opts(&self, s: &str, opts: &CompileOptions) -> Result<Pattern, GrokError> {
+        let expansion = self.expand_all(s, opts)?;
+        Self::build(expansion, opts)
+    }
+
+    /// compile the expansion into a Pattern with the options applied.
+    fn build(expansion: Expansion, opts: &CompileOptions) -> Result<Pattern, GrokError> {
+        if opts.max_array_len == Some(0) {
+            return Err(GrokError::InvalidOption(
+                "max_array_len must be at least 1".to_string(),
+            ));
+        }
+        let mut pattern = Pattern::compile_expanded(&expansion.regex, expansion.alias)?;
+        pattern.descriptions = expansion.descriptions;
+        pattern.inferred = expansion.inferred;
+        pattern.original_field = opts.keep_original_field.as_deref().map(Arc::from);
+        pattern.collect_arrays = opts.collect_arrays;
+        pattern.max_array_len = opts.max_array_len;
+        pattern.array_overflow = opts.array_overflow;
+        pattern.defaults = opts
+            .defaults
+            .iter()
+            .map(|(field, value)| (Arc::from(field.as_str()), value.clone()))
+            .collect();
+        Ok(pattern)
+    }
+
+    /// Compile the pattern with the default values of the fields which are not captured, e.g. `destination.ip`
+    /// when the domain branch of `%{NGINX_HOST}` is taken, so the result always has the keys.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use grok_rs::{Grok, Value};
+    ///
+    /// let grok = Grok::default();
+    /// let defaults = HashMap::from([("ip".to_string(), Value::String("0.0.0.0".into()))]);
+    /// let pattern = grok.compile_with_defaults("%{IP:ip}|%{HOSTNAME:host}", true, defaults).unwrap();
+    /// let result = pattern.parse("example.com").unwrap();
+    /// assert_eq!(Some(&Value::String("0.0.0.0".into())), result.get("ip"));
+    /// ```
+    pub fn compile_with_defaults(
+        &self,
+        s: &str,
+        named_capture_only: bool,
+        defaults: HashMap<String, Value>,
+    ) -> Result<Pattern, GrokError> {
+        let mut opts = CompileOptions::default().named_capture_only(named_capture_only);
+        opts.defaults = defaults;
+        self.compile_with_opts(s, &opts)
+    }
+
+    /// Compile the pattern with the given options, and return the Pattern with the fields it produces,
+    /// and the warnings worth a look, e.g. a field captured by several groups where only the last one wins.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use grok_rs::{CompileOptions, Grok};
+    ///
+    /// let grok = Grok::default();
+    /// let opts = CompileOptions::default().named_capture_only(true);
+    /// let result = grok.compile_detailed("%{IP:ip} %{IP:ip}", &opts).unwrap();
+    /// assert_eq!(1, result.fields.len());
+    /// assert_eq!("ip", result.fields[0].name);
+    /// assert!(!result.warnings.is_empty());
+    /// ```
+    pub fn compile_detailed(
+        &self,
+        s: &str,
+        opts: &CompileOptions,
+    ) -> Result<CompileResult, GrokError> {
+        let expansion = self.expand_all(s, opts)?;
+        let iterations = expansion.iterations;
+        let pattern = Self::build(expansion, opts)?;
+        Ok(CompileResult {
+            fields: pattern.schema(),
+            warnings: pattern.warnings(),
+            pattern,
+            iterations,
+        })
+    }
+
+    /// Expand the pattern and estimate its cost, e.g. to warn before deploying an expensive pattern.
+    /// The more capture groups and alternations, the slower the matching in general.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let complexity = grok.complexity("%{WORD:w}", true).unwrap();
+    /// assert_eq!(1, complexity.capture_groups);
+    /// assert_eq!(0, complexity.alternations);
+    /// ```
+    pub fn complexity(
+        &self,
+        expr: &str,
+        named_capture_only: bool,
+    ) -> Result<Complexity, GrokError> {
+        let opts = CompileOptions::default().named_capture_only(named_capture_only);
+        let (regex, _) = self.expand(expr, &opts)?;
+        Ok(complexity_of(&regex))
+    }
+
+    /// Expand the placeholders of the pattern recursively, and return the final regex and the alias map.
+    /// The alias map is keyed by the generated capture group name, and the value is the alias and the type.
+    ///
+    /// The result can be cached, e.g. at build time, and compiled later by [`Pattern::compile_expanded`],
+    /// which also collects the `@key` and `@value` pairs into [`Value::Map`].
+    /// The inferred types of [`CompileOptions::infer_types_by_name`] are kept in the alias map as the explicit ones,
+    /// so the value which can't be converted is an error of the compiled pattern, not kept as string.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use grok_rs::{CompileOptions, Grok};
+    ///
+    /// let grok = Grok::default();
+    /// let opts = CompileOptions::default().named_capture_only(true);
+    /// let (regex, alias) = grok.expand("%{USERNAME:user}", &opts).unwrap();
+    /// assert_eq!("(?<name0>[a-zA-Z0-9._-]+)", regex);
+    /// assert_eq!(Some(&("user".to_string(), None)), alias.get("name0"));
+    /// ```
+    pub fn expand(
+        &self,
+        s: &str,
+        opts: &CompileOptions,
+    ) -> Result<(String, HashMap<String, AliasType>), GrokError> {
+        let expansion = self.expand_all(s, opts)?;
+        Ok((expansion.regex, expansion.alias))
+    }
+
+    /// expand the placeholders like [`Grok::expand`], and keep the metadata of the placeholders as well.
+    fn expand_all(&self, s: &str, opts: &CompileOptions) -> Result<Expansion, GrokError> {
+        check_placeholders(s)?;
+
+        let named_capture_only = opts.named_capture_only;
+        let mut alias_map = HashMap::new();
+        let mut descriptions = HashMap::new();
+        let mut inferred = HashSet::new();
+        let mut haystack = if opts.exact {
+            GROK_REGEX
+                .replace_all(s, r"\b{start-half}(?:$0)\b{end-half}")
+                .into_owned()
+        } else {
+            s.to_string()
+        };
+        let mut index = 0;
+        let mut iter_left = MAX_RECURSION;
+
+        while let Some(caps) = GROK_REGEX.captures(haystack.clone().as_str()) {
+            if iter_left <= 0 {
+                return Err(GrokError::RecursionLimit(MAX_RECURSION));
+            }
+            iter_left -= 1;
+
+            let name = &caps[NAME_INDEX];
+            let pattern = &caps[PATTERN_INDEX];
+
+            let pattern_regex = self
+                .pattern_definition(pattern)
+                .ok_or_else(|| GrokError::PatternNotFound(pattern.to_string()))?;
+            check_placeholders(pattern_regex)?;
+            let pattern_regex = match pattern_regex {
+                ".*" if opts.lazy_greedydata && pattern == "GREEDYDATA" => ".*?",
+                _ => pattern_regex,
+            };
+
+            let explicit_type = caps
+                .get(TYPE_INDEX)
+                .map(|m| m.as_str())
+                .or(self.pattern_types.get(pattern).map(|t| t.as_str()));
+            let inferred_type = caps
+                .get(ALIAS_INDEX)
+                .and_then(|alias| opts.inferred_type(alias.as_str()))
+                .filter(|_| explicit_type.is_none());
+            let type_ = explicit_type
+                .or(inferred_type)
+                .map(|t| self.resolve_type(t))
+                .transpose()?;
+            if let (Some(alias), Some(description)) =
+                (caps.get(ALIAS_INDEX), caps.get(DESCRIPTION_INDEX))
+            {
+                descriptions
+                    .entry(alias.as_str().to_string())
+                    .or_insert_with(|| description.as_str().to_string());
+            }
+            let to_replace = format!("%{{{name}}}");
+
+            // the pattern referencing itself by the same placeholder, e.g. `A x%{A}`, would be replaced forever,
+            // the longer cycles, e.g. `A %{B}` and `B %{A}`, are stopped by the rounds
+            if pattern_regex.contains(&to_replace) {
+                return Err(GrokError::RecursionLimit(MAX_RECURSION));
+            }
+            while haystack.contains(&to_replace) {
+                let replacement = match caps.get(ALIAS_INDEX) {
+                    None if named_capture_only => {
+                        format!("(?:{pattern_regex})")
+                    }
+                    _ => {
+                        let new_name = format!("name{index}");
+                        let origin_alias =
+                            caps.get(ALIAS_INDEX).map(|m| m.as_str()).unwrap_or(pattern);
+                        alias_map
+                            .insert(new_name.clone(), (origin_alias.to_string(), type_.clone()));
+                        if inferred_type.is_some() {
+                            inferred.insert(new_name.clone());
+                        }
+                        format!("(?<{new_name}>{pattern_regex})")
+                    }
+                };
+
+                haystack = haystack.replacen(&to_replace, &replacement, 1);
+                index += 1;
+            }
+        }
+
+        haystack.insert_str(0, &opts.flags());
+        Ok(Expansion {
+            regex: haystack,
+            alias: alias_map,
+            descriptions,
+            iterations: (MAX_RECURSION - iter_left) as usize,
+            inferred,
+        })
+    }
+}
+
+/// the result of [`Grok::expand_all`].
+struct Expansion {
+    regex: String,
+    alias: HashMap<String, AliasType>,
+    /// the description of each field, the first one wins if a field is described several times.
+    descriptions: HashMap<String, String>,
+    /// the rounds of the expansion, which is limited by `MAX_RECURSION`.
+    iterations: usize,
+    /// the groups whose type is inferred from the alias.
+    inferred: HashSet<String>,
+}
+
+impl<T: Into<String>> FromIterator<(T, T)> for Grok {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut grok = Grok::default();
+        for (k, v) in iter {
+            grok.add_pattern(k, v);
+        }
+        grok
+    }
+}
+
+impl<S: Into<String>, const N: usize> From<[(S, S); N]> for Grok {
+    fn from(arr: [(S, S); N]) -> Self {
+        Self::from_iter(arr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Case<'a> {
+        patterns: Vec<(&'a str, &'a str)>,
+        pattern: &'a str,
+        input: &'a str,
+        expected: HashMap<String, Value>,
+        named_capture_only: bool,
+    }
+
+    fn assert(c: Case<'_>) {
+        let grok = Grok::from_iter(c.patterns);
+        let pattern = grok.compile(c.pattern, c.named_capture_only).unwrap();
+        assert_eq!(c.expected, pattern.parse(c.input).unwrap());
+    }
+
+    fn asserts(cases: Vec<Case<'_>>) {
+        for c in cases {
+            assert(c);
+        }
+    }
+
+    #[test]
+    fn test_simple_add_pattern() {
+        let mut grok = Grok::default();
+        grok.add_pattern("NAME", r"[A-z0-9._-]+");
+        let pattern = grok.compile("%{NAME}", false).unwrap();
+        let expected: HashMap<String, Value> = [("NAME", "admin")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+            .collect();
+
+        assert_eq!(expected, pattern.parse("admin").unwrap());
+        assert_eq!(expected, pattern.parse("admin user").unwrap());
+    }
+
+    #[test]
+    fn test_named_capture_only() {
+        let grok = Grok::default();
+        let pattern = grok
+            // USERNAME and EMAILADDRESS are defined in grok-patterns
+            .compile("%{USERNAME} %{EMAILADDRESS:email}", true)
+            .unwrap();
+
+        let expected = [("email", "admin@example.com")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+            .collect::<HashMap<String, Value>>();
+
+        assert_eq!(expected, pattern.parse("admin admin@example.com").unwrap());
+    }
+
+    #[test]
+    fn test_from() {
+        let expected = [("NAME", "admin")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+            .collect::<HashMap<String, Value>>();
+
+        {
+            let grok = Grok::from_iter([("NAME", r"[A-z0-9._-]+")]);
+            let pattern = grok.compile("%{NAME}", false).unwrap();
+            assert_eq!(expected, pattern.parse("admin").unwrap());
+        }
+        {
+            let grok = Grok::from([("NAME", r"[A-z0-9._-]+")]);
+            let pattern = grok.compile("%{NAME}", false).unwrap();
+            assert_eq!(expected, pattern.parse("admin").unwrap());
+        }
+    }
+
+    #[test]
+    fn test_pattern_parse_no_captures() {
+        let grok = Grok::default();
+        let pattern = grok.compile("%{USERNAME}", false).unwrap();
+
+        assert!(pattern.parse("$#@").unwrap().is_empty());
+        assert!(pattern.parse("").unwrap().is_empty());
+        assert!(pattern.parse("✅🚀🌍").unwrap().is_empty());
+        assert!(pattern.parse("     ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_composite_or_pattern() {
+        let mut grok = Grok::default();
+        grok.add_pattern("MAC", r"(?:%{CISCOMAC}|%{WINDOWSMAC}|%{COMMONMAC})");
+        grok.add_pattern("CISCOMAC", r"(?:(?:[A-Fa-f0-9]{4}\.){2}[A-Fa-f0-9]{4})");
+        grok.add_pattern("WINDOWSMAC", r"(?:(?:[A-Fa-f0-9]{2}-){5}[A-Fa-f0-9]{2})");
+        grok.add_pattern("COMMONMAC", r"(?:(?:[A-Fa-f0-9]{2}:){5}[A-Fa-f0-9]{2})");
+
+        let pattern = grok.compile("%{MAC}", false).unwrap();
+        let expected = [
+            ("MAC", "5E:FF:56:A2:AF:15"),
+            ("COMMONMAC", "5E:FF:56:A2:AF:15"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+        .collect::<HashMap<String, Value>>();
+
+        assert_eq!(expected, pattern.parse("5E:FF:56:A2:AF:15").unwrap());
+        assert_eq!(
+            expected,
+            pattern.parse("127.0.0.1 5E:FF:56:A2:AF:15").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_multiple_patterns() {
+        let mut grok = Grok::default();
+        grok.add_pattern("YEAR", r"(\d\d){1,2}");
+        grok.add_pattern("MONTH", r"\b(?:Jan(?:uary)?|Feb(?:ruary)?|Mar(?:ch)?|Apr(?:il)?|May|Jun(?:e)?|Jul(?:y)?|Aug(?:ust)?|Sep(?:tember)?|Oct(?:ober)?|Nov(?:ember)?|Dec(?:ember)?)\b");
+        grok.add_pattern("DAY", r"(?:Mon(?:day)?|Tue(?:sday)?|Wed(?:nesday)?|Thu(?:rsday)?|Fri(?:day)?|Sat(?:urday)?|Sun(?:day)?)");
+        let pattern = grok.compile("%{DAY} %{MONTH} %{YEAR}", false).unwrap();
+
+        let expected = [("DAY", "Monday"), ("MONTH", "March"), ("YEAR", "2012")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+            .collect::<HashMap<String, Value>>();
+        assert_eq!(expected, pattern.parse("Monday March 2012").unwrap());
+    }
+
+    #[test]
+    fn test_adhoc_pattern() {
+        let grok = Grok::default();
+        let pattern = grok.compile(r"\[(?<threadname>[^\]]+)\]", false).unwrap();
+        let expected = [("threadname", "thread1")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+            .collect::<HashMap<String, Value>>();
+        assert_eq!(expected, pattern.parse("[thread1]").unwrap());
+    }
+
+    #[test]
+    fn test_type() {
+        let mut grok = Grok::default();
+        grok.add_pattern("NUMBER", r"\d+");
+
+        // int
+        {
+            let pattern = grok.compile("%{NUMBER:digit:int}", false).unwrap();
+            let expected = [("digit", Value::Int(123))]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect::<HashMap<String, Value>>();
+            assert_eq!(expected, pattern.parse("hello 123").unwrap());
+        }
+
+        // float
+        {
+            let pattern = grok.compile("%{NUMBER:digit:float}", false).unwrap();
+            let expected = [("digit", Value::Float(123.0))]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect::<HashMap<String, Value>>();
+            assert_eq!(expected, pattern.parse("hello 123.0").unwrap());
+        }
+
+        // wrong type
+        {
+            let pattern = grok.compile("%{NUMBER:digit:wrong}", false);
+            assert_eq!(
+                GrokError::UnknownType("wrong".to_string()),
+                pattern.unwrap_err()
+            );
+        }
+
+        {
+            // wrong value
+            let pattern = grok.compile("%{USERNAME:digit:float}", false).unwrap();
+            assert_eq!(
+                Err(GrokError::Conversion {
+                    value: "grok".to_string(),
+                    reason: "invalid float literal".to_string(),
+                }),
+                pattern.parse("grok")
+            );
+        }
+    }
+
+    #[test]
+    fn test_compile_options() {
+        let grok = Grok::default();
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .case_insensitive(true)
+            .unicode(false);
+
+        let pattern = grok.compile_with_opts("GET %{WORD:path}", &opts).unwrap();
+        let expected = HashMap::from([("path".to_string(), Value::String("index".into()))]);
+        assert_eq!(expected, pattern.parse("get index").unwrap());
+
+        // the same options are reused for another expression
+        let pattern = grok
+            .compile_with_opts("%{USERNAME} %{INT:code:int}", &opts)
+            .unwrap();
+        let expected = HashMap::from([("code".to_string(), Value::Int(42))]);
+        assert_eq!(expected, pattern.parse("ADMIN 42").unwrap());
+        // unicode is disabled, so `\w` doesn't match the non-ASCII word
+        assert!(grok
+            .compile_with_opts("^%{WORD:w}$", &opts)
+            .unwrap()
+            .parse("héllo")
+            .unwrap()
+            .is_empty());
+
+        // dot matches new line
+        let opts = CompileOptions::default().dot_matches_new_line(true);
+        let pattern = grok
+            .compile_with_opts("%{GREEDYDATA:message}", &opts)
+            .unwrap();
+        let expected = HashMap::from([("message".to_string(), Value::String("a\nb".into()))]);
+        assert_eq!(expected, pattern.parse("a\nb").unwrap());
+    }
+
+    #[test]
+    fn test_pattern_definition() {
+        let mut grok = Grok::default();
+        assert_eq!(
+            Some(r"[a-zA-Z0-9._-]+"),
+            grok.pattern_definition("USERNAME")
+        );
+
+        grok.add_pattern("NAME", r"[A-z0-9._-]+");
+        assert_eq!(Some(r"[A-z0-9._-]+"), grok.pattern_definition("NAME"));
+
+        // user-defined pattern overrides the default one
+        grok.add_pattern("USERNAME", r"\w+");
+        assert_eq!(Some(r"\w+"), grok.pattern_definition("USERNAME"));
+
+        assert_eq!(None, grok.pattern_definition("NOT_EXIST"));
+    }
+
+    #[test]
+    fn test_malformed_placeholder() {
+        let grok = Grok::default();
+
+        let err = grok.compile("%{IP:my ip}", false).unwrap_err();
+        assert_eq!(
+            GrokError::MalformedPlaceholder {
+                text: "%{IP:my ip}".to_string(),
+                position: 0,
+            },
+            err
+        );
+        assert_eq!(
+            "malformed placeholder `%{IP:my ip}` at position 0",
+            err.to_string()
+        );
+
+        assert_eq!(
+            Err(GrokError::MalformedPlaceholder {
+                text: "%{WORD:a/b}".to_string(),
+                position: 9,
+            }),
+            grok.compile("%{IP:ip} %{WORD:a/b}", false).map(|_| ())
+        );
+        assert_eq!(
+            Err(GrokError::MalformedPlaceholder {
+                text: "%{IP".to_string(),
+                position: 0,
+            }),
+            grok.compile("%{IP", false).map(|_| ())
+        );
+
+        // the malformed placeholder is in the definition of the referenced pattern
+        let mut grok = Grok::default();
+        grok.add_pattern("HOST", r"%{IPORHOST:host name}");
+        assert_eq!(
+            Err(GrokError::MalformedPlaceholder {
+                text: "%{IPORHOST:host name}".to_string(),
+                position: 0,
+            }),
+            grok.compile("%{HOST}", false).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_parse_tuple() {
+        let mut grok = Grok::default();
+        grok.add_pattern(
+            "NGINX_HOST",
+            r"(?:%{IP:destination.ip}|%{NGINX_NOTSEPARATOR:destination.domain})(:%{NUMBER:destination.port:int})?",
+        );
+        grok.add_pattern("NGINX_NOTSEPARATOR", r"[^\t ,:]+");
+        let pattern = grok.compile("%{NGINX_HOST}", true).unwrap();
+
+        assert_eq!(
+            Some((Value::String("127.0.0.1".into()), Value::Int(1234))),
+            pattern
+                .parse_tuple2("127.0.0.1:1234", "destination.ip", "destination.port")
+                .unwrap()
+        );
+        // the domain branch is taken, so the ip is not captured
+        assert_eq!(
+            None,
+            pattern
+                .parse_tuple2("example.com:1234", "destination.ip", "destination.port")
+                .unwrap()
+        );
+        assert_eq!(
+            None,
+            pattern
+                .parse_tuple2("", "destination.ip", "destination.port")
+                .unwrap()
+        );
+
+        // the default value and the original input like `parse`
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .default_value("ip", Value::String("0.0.0.0".into()))
+            .keep_original_field("raw");
+        let fallback = grok
+            .compile_with_opts("%{IP:ip}|%{HOSTNAME:host}", &opts)
+            .unwrap();
+        let host = Value::String("example.com".into());
+        assert_eq!(
+            Some((host.clone(), Value::String("0.0.0.0".into()))),
+            fallback.parse_tuple2("example.com", "host", "ip").unwrap()
+        );
+        assert_eq!(
+            Some((host.clone(), host.clone())),
+            fallback.parse_tuple2("example.com", "host", "raw").unwrap()
+        );
+
+        let pattern = grok
+            .compile("%{WORD:method} %{URIPATH:path} %{INT:status:int}", true)
+            .unwrap();
+        assert_eq!(
+            Some((
+                Value::String("GET".into()),
+                Value::String("/index.html".into()),
+                Value::Int(200)
+            )),
+            pattern
+                .parse_tuple3("GET /index.html 200", "method", "path", "status")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_value() {
+        let map = HashMap::from([
+            ("port".to_string(), Value::String("8080".into())),
+            ("code".to_string(), Value::Int(-1)),
+            ("ip".to_string(), Value::String("::1".into())),
+        ]);
+
+        assert_eq!(Ok(8080u16), u16::from_field(&map, "port"));
+        assert_eq!(Ok(-1i32), i32::from_field(&map, "code"));
+        assert!(u32::from_field(&map, "code").is_err());
+        assert_eq!(
+            Ok(std::net::Ipv6Addr::LOCALHOST),
+            std::net::Ipv6Addr::from_field(&map, "ip")
+        );
+        assert_eq!(Ok(None), Option::<String>::from_field(&map, "missing"));
+        assert_eq!(Ok(vec![8080u16]), Vec::<u16>::from_field(&map, "port"));
+        assert_eq!(
+            Ok(vec![1, 2]),
+            Vec::<i64>::from_value(&Value::Array(vec![Value::Int(1), Value::Int(2)]))
+        );
+        assert_eq!(
+            Err(GrokError::MissingField("missing".to_string())),
+            String::from_field(&map, "missing")
+        );
+    }
+
+    #[test]
+    fn test_expand() {
+        let grok = Grok::default();
+        let opts = CompileOptions::default().case_insensitive(true);
+        let expr = "%{IP:client} %{WORD:method} %{NUMBER:bytes:int}";
+
+        let (regex, alias) = grok.expand(expr, &opts).unwrap();
+        assert!(regex.starts_with("(?i)"));
+        assert!(!regex.contains("%{"));
+        assert_eq!(
+            Some(&("bytes".to_string(), Some("int".to_string()))),
+            alias.values().find(|(alias, _)| alias == "bytes")
+        );
+
+        let input = "55.3.244.1 get 15824";
+        let expanded = Pattern::compile_expanded(&regex, alias).unwrap();
+        let compiled = grok.compile_with_opts(expr, &opts).unwrap();
+        assert_eq!(
+            compiled.parse(input).unwrap(),
+            expanded.parse(input).unwrap()
+        );
+        assert_eq!(
+            Some(&Value::String("get".to_string())),
+            expanded.parse(input).unwrap().get("method")
+        );
+
+        assert!(matches!(
+            Pattern::compile_expanded("(", HashMap::new()),
+            Err(GrokError::Regex(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile("^%{WORD:method} %{URIPATH:path}\n%{INT:status:int}$", true)
+            .unwrap();
+
+        let input = "GET /index.html\n200\n---\nPOST /login\n401\n---\n";
+        let results = pattern
+            .parse_records(input, "\n---\n")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let expected = vec![
+            HashMap::from([
+                ("method".to_string(), Value::String("GET".into())),
+                ("path".to_string(), Value::String("/index.html".into())),
+                ("status".to_string(), Value::Int(200)),
+            ]),
+            HashMap::from([
+                ("method".to_string(), Value::String("POST".into())),
+                ("path".to_string(), Value::String("/login".into())),
+                ("status".to_string(), Value::Int(401)),
+            ]),
+        ];
+        assert_eq!(expected, results);
+
+        // the whole input can't be matched as one record since `$` doesn't match before `---`
+        assert!(pattern.parse(input).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_match_count() {
+        let grok = Grok::default();
+        let pattern = grok.compile("ERROR-%{INT}", false).unwrap();
+        assert_eq!(
+            3,
+            pattern.match_count("ERROR-1 ok ERROR-22 WARN-3 ERROR-333")
+        );
+        assert_eq!(0, pattern.match_count("no error at all"));
+
+        // the zero-width matches are not counted
+        let pattern = grok.compile("%{DATA}", false).unwrap();
+        assert_eq!(0, pattern.match_count("abc"));
+    }
+
+    #[test]
+    fn test_parse_interned() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile("%{IP:client} %{NUMBER:bytes:int}", true)
+            .unwrap();
+
+        let a = pattern.parse_interned("10.0.0.1 100").unwrap();
+        let b = pattern.parse_interned("10.0.0.2 200").unwrap();
+        assert_eq!(Some(&Value::Int(100)), a.get("bytes"));
+        assert_eq!(Some(&Value::Int(200)), b.get("bytes"));
+
+        for (key, _) in a.iter() {
+            let (other, _) = b.get_key_value(key).unwrap();
+            assert!(Arc::ptr_eq(key, other));
+        }
+
+        let expected = pattern.parse("10.0.0.1 100").unwrap();
+        let interned = a
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(expected, interned);
+        assert!(pattern.parse_interned("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile("%{IP:client} %{WORD:method} %{NUMBER:bytes:int}", true)
+            .unwrap();
+
+        assert_eq!(Ok(()), pattern.validate("55.3.244.1 GET 15824"));
+
+        // parse succeeds with the trailing garbage, but validate rejects it
+        let input = "55.3.244.1 GET 15824 trailing garbage";
+        assert!(!pattern.parse(input).unwrap().is_empty());
+        assert_eq!(
+            Err(GrokError::PartialMatch { start: 0, end: 20 }),
+            pattern.validate(input)
+        );
+
+        assert_eq!(Err(GrokError::NoMatch), pattern.validate("nothing"));
+        assert!(matches!(
+            pattern.validate("55.3.244.1 GET 1.5"),
+            Err(GrokError::Conversion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_monthnum_daynum() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile("%{MONTH:month:monthnum}|%{MONTHNUM:month:monthnum}", true)
+            .unwrap();
+        for input in ["March", "Mar", "03", "3", "März"] {
+            assert_eq!(
+                Some(&Value::Int(3)),
+                pattern.parse(input).unwrap().get("month"),
+                "{input}"
+            );
+        }
+
+        let pattern = grok.compile("%{NOTSPACE:month:monthnum}", true).unwrap();
+        assert_eq!(
+            Err(GrokError::Conversion {
+                value: "Marchy".to_string(),
+                reason: "invalid month".to_string(),
+            }),
+            pattern.parse("Marchy")
+        );
+        assert!(pattern.parse("13").is_err());
+        assert!(pattern.parse("0").is_err());
+
+        let pattern = grok.compile("%{DAY:day:daynum}", true).unwrap();
+        assert_eq!(
+            Some(&Value::Int(1)),
+            pattern.parse("Monday").unwrap().get("day")
+        );
+        assert_eq!(
+            Some(&Value::Int(7)),
+            pattern.parse("Sun").unwrap().get("day")
+        );
+        let pattern = grok.compile("%{WORD:day:daynum}", true).unwrap();
+        assert!(pattern.parse("Someday").is_err());
+    }
+
+    #[test]
+    fn test_type_alias() {
+        let mut grok = Grok::default();
+        grok.register_type_alias("integer", "int");
+        grok.register_type_alias("double_precision", "double");
+        grok.register_type_alias("broken", "not_a_type");
+
+        let alias = grok.compile("%{NUMBER:n:integer}", true).unwrap();
+        let target = grok.compile("%{NUMBER:n:int}", true).unwrap();
+        for input in ["42", "-7"] {
+            assert_eq!(target.parse(input), alias.parse(input));
+        }
+        // the alias is validated like the target type
+        assert_eq!(target.parse("1.5"), alias.parse("1.5"));
+        assert!(alias.parse("1.5").is_err());
+
+        let pattern = grok.compile("%{NUMBER:n:double_precision}", true).unwrap();
+        assert_eq!(
+            Some(&Value::Float(1.5)),
+            pattern.parse("1.5").unwrap().get("n")
+        );
+
+        assert_eq!(
+            GrokError::UnknownType("broken".to_string()),
+            grok.compile("%{NUMBER:n:broken}", true).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_keep_original_field() {
+        let grok = Grok::default();
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .keep_original_field("message");
+        let pattern = grok
+            .compile_with_opts("%{WORD:method} %{INT:status:int}", &opts)
+            .unwrap();
+
+        let input = "GET 200 OK";
+        let expected = HashMap::from([
+            ("method".to_string(), Value::String("GET".into())),
+            ("status".to_string(), Value::Int(200)),
+            ("message".to_string(), Value::String(input.into())),
+        ]);
+        assert_eq!(expected, pattern.parse(input).unwrap());
+        assert_eq!(
+            Some(&Value::String(input.into())),
+            pattern.parse_interned(input).unwrap().get("message")
+        );
+        // not matched, so nothing is kept
+        assert!(pattern.parse("-").unwrap().is_empty());
+
+        // the captured field takes precedence
+        let pattern = grok
+            .compile_with_opts("%{WORD:method} %{GREEDYDATA:message}", &opts)
+            .unwrap();
+        assert_eq!(
+            Some(&Value::String("200 OK".into())),
+            pattern.parse(input).unwrap().get("message")
+        );
+    }
+
+    #[test]
+    fn test_exact() {
+        let grok = Grok::default();
+        let input = "5E:FF:56:A2:AF:15FF";
+
+        // the MAC matches the prefix of the longer hex blob
+        let pattern = grok.compile("%{MAC:mac}", true).unwrap();
+        assert_eq!(
+            Some(&Value::String("5E:FF:56:A2:AF:15".into())),
+            pattern.parse(input).unwrap().get("mac")
+        );
+
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .exact(true);
+        let pattern = grok.compile_with_opts("%{MAC:mac}", &opts).unwrap();
+        assert!(pattern.parse(input).unwrap().is_empty());
+        assert_eq!(
+            Some(&Value::String("5E:FF:56:A2:AF:15".into())),
+            pattern
+                .parse("mac=5E:FF:56:A2:AF:15 up")
+                .unwrap()
+                .get("mac")
+        );
+
+        // the pattern starting with a non-word character still matches
+        let pattern = grok
+            .compile_with_opts("%{WORD:k}=%{QUOTEDSTRING:v}", &opts)
+            .unwrap();
+        assert_eq!(
+            Some(&Value::String("\"b c\"".into())),
+            pattern.parse("a=\"b c\"").unwrap().get("v")
+        );
+    }
+
+    #[test]
+    fn test_parse_sorted() {
+        let mut grok = Grok::default();
+        grok.add_pattern(
+            "NGINX_HOST",
+            r"(?:%{IP:destination.ip}|%{NGINX_NOTSEPARATOR:destination.domain})(:%{NUMBER:destination.port})?",
+        );
+        grok.add_pattern("NGINX_NOTSEPARATOR", r"[^\t ,:]+");
+        let pattern = grok.compile("%{NGINX_HOST}", false).unwrap();
+
+        let expected = vec![
+            ("BASE10NUM", "1234"),
+            ("IPV4", "127.0.0.1"),
+            ("NGINX_HOST", "127.0.0.1:1234"),
+            ("destination.ip", "127.0.0.1"),
+            ("destination.port", "1234"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
+        .collect::<Vec<_>>();
+        assert_eq!(expected, pattern.parse_sorted("127.0.0.1:1234").unwrap());
+        assert!(pattern.parse_sorted("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unused_groups() {
+        let grok = Grok::default();
+
+        let simple = grok.compile("%{WORD:method} %{INT:status}", true).unwrap();
+        assert_eq!(0, simple.unused_groups());
+
+        // the IPV6 pattern has lots of inner groups
+        let nested = grok.compile("%{IP:client} %{WORD:method}", true).unwrap();
+        assert!(nested.unused_groups() > 10);
+
+        // the unnamed ad-hoc group is never read
+        let adhoc = grok.compile(r"(\d+)-%{WORD:w}", true).unwrap();
+        assert_eq!(1, adhoc.unused_groups());
+    }
+
+    #[test]
+    fn test_parse_or() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile("%{IP:client} %{WORD:method} %{INT:status:int}", true)
+            .unwrap();
+        let fallback = grok.compile("%{GREEDYDATA:message}", true).unwrap();
+
+        let result = pattern.parse_or("10.0.0.1 GET 200", &fallback).unwrap();
+        assert_eq!(Some(&Value::Int(200)), result.get("status"));
+        assert_eq!(None, result.get("message"));
+
+        let input = "kernel: out of memory";
+        let expected = HashMap::from([("message".to_string(), Value::String(input.into()))]);
+        assert_eq!(expected, pattern.parse_or(input, &fallback).unwrap());
+
+        // the pattern matches without any capture, so the fallback is not used
+        let pattern = grok.compile("%{WORD}", true).unwrap();
+        assert!(pattern.parse_or(input, &fallback).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lazy_greedydata() {
+        let grok = Grok::default();
+        let expr = "%{GREEDYDATA:head} %{NUMBER:n:int}";
+        let input = "a b 1 2";
+
+        // the greedy one swallows the first number
+        let pattern = grok.compile(expr, true).unwrap();
+        let expected = HashMap::from([
+            ("head".to_string(), Value::String("a b 1".into())),
+            ("n".to_string(), Value::Int(2)),
+        ]);
+        assert_eq!(expected, pattern.parse(input).unwrap());
+
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .lazy_greedydata(true);
+        let pattern = grok.compile_with_opts(expr, &opts).unwrap();
+        let expected = HashMap::from([
+            ("head".to_string(), Value::String("a b".into())),
+            ("n".to_string(), Value::Int(1)),
+        ]);
+        assert_eq!(expected, pattern.parse(input).unwrap());
+
+        // the user-defined GREEDYDATA is kept
+        let mut grok = Grok::default();
+        grok.add_pattern("GREEDYDATA", r"[a-z ]*");
+        let pattern = grok.compile_with_opts(expr, &opts).unwrap();
+        assert_eq!(
+            Some(&Value::String("a b".into())),
+            pattern.parse("a b 1").unwrap().get("head")
+        );
+    }
+
+    #[test]
+    fn test_collect_arrays() {
+        let grok = Grok::default();
+        let expr = r"(?:%{WORD:tag}\s*)(?:%{WORD:tag}\s*)?(?:%{WORD:tag}\s*)?(?:%{WORD:tag}\s*)?";
+        let input = "a b c d";
+        let tags = |tags: &[&str]| {
+            Value::Array(
+                tags.iter()
+                    .map(|tag| Value::String(tag.to_string()))
+                    .collect(),
+            )
+        };
+
+        // the last one wins by default
+        let pattern = grok.compile(expr, true).unwrap();
+        assert_eq!(
+            Some(&Value::String("d".into())),
+            pattern.parse(input).unwrap().get("tag")
+        );
+
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .collect_arrays(true);
+        let pattern = grok.compile_with_opts(expr, &opts).unwrap();
+        assert_eq!(
+            Some(&tags(&["a", "b", "c", "d"])),
+            pattern.parse(input).unwrap().get("tag")
+        );
+        // captured only once, so it's not an array
+        assert_eq!(
+            Some(&Value::String("a".into())),
+            pattern.parse("a").unwrap().get("tag")
+        );
+
+        let pattern = grok
+            .compile_with_opts(expr, &opts.clone().max_array_len(2, ArrayOverflow::Error))
+            .unwrap();
+        assert_eq!(
+            Err(GrokError::ArrayTooLong {
+                field: "tag".to_string(),
+                max: 2,
+            }),
+            pattern.parse(input)
+        );
+        assert_eq!(
+            Some(&tags(&["a", "b"])),
+            pattern.parse("a b").unwrap().get("tag")
+        );
+
+        let pattern = grok
+            .compile_with_opts(
+                expr,
+                &opts.clone().max_array_len(2, ArrayOverflow::Truncate),
+            )
+            .unwrap();
+        assert_eq!(
+            Some(&tags(&["a", "b"])),
+            pattern.parse(input).unwrap().get("tag")
+        );
+        assert_eq!(
+            Some(&tags(&["a", "b"])),
+            pattern.parse_interned(input).unwrap().get("tag")
+        );
+
+        // a single value can't be limited to nothing
+        assert!(matches!(
+            grok.compile_with_opts(expr, &opts.max_array_len(0, ArrayOverflow::Error)),
+            Err(GrokError::InvalidOption(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_stripping_ansi() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile(
+                "^%{TIMESTAMP_ISO8601:ts} %{LOGLEVEL:level} %{GREEDYDATA:message}$",
+                true,
+            )
+            .unwrap();
+        let input =
+            "\x1b[2m2024-06-05T10:00:00Z\x1b[0m \x1b[1;31mERROR\x1b[0m \x1b[4mdisk\x1b[24m full";
+
+        // the color codes break the match
+        assert!(pattern.parse(input).unwrap().is_empty());
+
+        let expected = HashMap::from([
+            (
+                "ts".to_string(),
+                Value::String("2024-06-05T10:00:00Z".into()),
+            ),
+            ("level".to_string(), Value::String("ERROR".into())),
+            ("message".to_string(), Value::String("disk full".into())),
+        ]);
+        assert_eq!(expected, pattern.parse_stripping_ansi(input).unwrap());
+    }
+
+    #[test]
+    fn test_compile_detailed() {
+        let mut grok = Grok::default();
+        grok.add_pattern(
+            "NGINX_HOST",
+            r"(?:%{IP:destination.ip}|%{NGINX_NOTSEPARATOR:destination.domain})(:%{NUMBER:destination.port:int})?",
+        );
+        grok.add_pattern("NGINX_NOTSEPARATOR", r"[^\t ,:]+");
+        let opts = CompileOptions::default().named_capture_only(true);
+        let result = grok.compile_detailed("%{NGINX_HOST}", &opts).unwrap();
+
+        assert_eq!(
+            vec![
+                FieldSchema {
+                    name: "destination.ip".to_string(),
+                    type_: None,
+                    description: None,
+                },
+                FieldSchema {
+                    name: "destination.domain".to_string(),
+                    type_: None,
+                    description: None,
+                },
+                FieldSchema {
+                    name: "destination.port".to_string(),
+                    type_: Some("int".to_string()),
+                    description: None,
+                },
+            ],
+            result.fields
+        );
+        assert_eq!(result.fields, result.pattern.schema());
+        // the IP internals and the port group are not named
+        assert_eq!(1, result.warnings.len());
+        assert!(result.warnings[0].ends_with("unnamed capture groups are never extracted"));
+
+        let opts = opts.keep_original_field("message");
+        let result = grok
+            .compile_detailed("%{NGINX_HOST} %{NUMBER:destination.port}", &opts)
+            .unwrap();
+        assert_eq!(4, result.fields.len());
+        assert_eq!("message", result.fields[3].name);
+        assert!(result.warnings.contains(
+            &"field `destination.port` is captured by 2 groups, the last captured value wins"
+                .to_string()
+        ));
+        assert!(result
+            .warnings
+            .contains(&"field `destination.port` is captured with different types".to_string()));
+    }
+
+    #[test]
+    fn test_ip_type() {
+        let grok = Grok::default();
+        let pattern = grok.compile("%{IP:addr:ip}", true).unwrap();
+        for (input, expected) in [
+            ("2001:0DB8:0000:0000:0000:0000:0000:0001", "2001:db8::1"),
+            ("2001:db8::1", "2001:db8::1"),
+            ("0:0:0:0:0:0:0:1", "::1"),
+            ("FE80::1", "fe80::1"),
+            ("192.168.0.1", "192.168.0.1"),
+        ] {
+            assert_eq!(
+                Some(&Value::String(expected.to_string())),
+                pattern.parse(input).unwrap().get("addr"),
+                "{input}"
+            );
+        }
+
+        let pattern = grok.compile("%{NOTSPACE:addr:ip}", true).unwrap();
+        assert_eq!(
+            Some(&Value::String("fe80::1%eth0".to_string())),
+            pattern.parse("FE80:0::1%eth0").unwrap().get("addr")
+        );
+        assert_eq!(
+            Err(GrokError::Conversion {
+                value: "example.com".to_string(),
+                reason: "invalid IP address".to_string(),
+            }),
+            pattern.parse("example.com")
+        );
     }
 
     #[test]
-    fn test_named_capture_only() {
+    fn test_duplicate_group() {
+        let grok = Grok::default();
+        assert_eq!(
+            Err(GrokError::DuplicateGroup {
+                name: "ip".to_string()
+            }),
+            grok.compile(r"(?<ip>\S+) %{IP:ip}", false).map(|_| ())
+        );
+        assert_eq!(
+            Err(GrokError::DuplicateGroup {
+                name: "ip".to_string()
+            }),
+            grok.compile(r"(?<ip>\S+) (?P<ip>\S+)", false).map(|_| ())
+        );
+        assert_eq!(
+            Err(GrokError::DuplicateGroup {
+                name: "name0".to_string()
+            }),
+            grok.compile(r"%{IP:ip} (?<name0>\S+)", false).map(|_| ())
+        );
+
+        // the escaped parentheses and the character classes are not groups
+        assert!(grok.compile(r"(?<ip>\S+) \(?<ip>[(?<ip>]", false).is_ok());
+        assert!(grok.compile(r"%{IP:ip} %{IP:ip}", false).is_ok());
+    }
+
+    #[test]
+    fn test_parse_ctx() {
         let grok = Grok::default();
         let pattern = grok
-            // USERNAME and EMAILADDRESS are defined in grok-patterns
-            .compile("%{USERNAME} %{EMAILADDRESS:email}", true)
+            .compile("%{IP:client} %{WORD:method}( %{NUMBER:status:int})?", true)
             .unwrap();
+        let mut ctx = ParseContext::new();
+
+        for input in [
+            "10.0.0.1 GET 200",
+            "10.0.0.2 POST",
+            "no match",
+            "10.0.0.3 PUT 201",
+        ] {
+            let expected = pattern.parse_interned(input).unwrap();
+            assert_eq!(
+                &expected,
+                pattern.parse_ctx(&mut ctx, input).unwrap(),
+                "{input}"
+            );
+        }
 
-        let expected = [("email", "admin@example.com")]
-            .into_iter()
-            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
-            .collect::<HashMap<String, Value>>();
+        let other = grok.compile("%{WORD:method}", true).unwrap();
+        assert_eq!(1, other.parse_ctx(&mut ctx, "GET").unwrap().len());
+    }
 
-        assert_eq!(expected, pattern.parse("admin admin@example.com").unwrap());
+    #[test]
+    fn test_complexity() {
+        let mut grok = Grok::default();
+        grok.add_pattern(
+            "NGINX_HOST",
+            r"(?:%{IP:destination.ip}|%{NGINX_NOTSEPARATOR:destination.domain})(:%{NUMBER:destination.port:int})?",
+        );
+        grok.add_pattern("NGINX_NOTSEPARATOR", r"[^\t ,:|]+");
+
+        let word = grok.complexity("%{WORD:w}", true).unwrap();
+        assert_eq!(
+            Complexity {
+                regex_len: r"(?<name0>\b\w+\b)".len(),
+                capture_groups: 1,
+                alternations: 0,
+            },
+            word
+        );
+
+        let host = grok.complexity("%{NGINX_HOST}", true).unwrap();
+        assert!(host.regex_len > word.regex_len);
+        assert!(host.capture_groups > word.capture_groups);
+        assert!(host.alternations > word.alternations);
+        // the `|` in the character class is not an alternation
+        let inner = grok.complexity("%{IP}%{NUMBER}", true).unwrap();
+        assert_eq!(host.alternations, inner.alternations + 1);
+
+        let unnamed = grok.complexity("%{NGINX_HOST}", false).unwrap();
+        assert!(unnamed.capture_groups > host.capture_groups);
+        assert_eq!(
+            Complexity {
+                regex_len: 11,
+                capture_groups: 1,
+                alternations: 1,
+            },
+            grok.complexity(r"(a|b)\(?:c)", true).unwrap()
+        );
     }
 
     #[test]
-    fn test_from() {
-        let expected = [("NAME", "admin")]
-            .into_iter()
-            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
-            .collect::<HashMap<String, Value>>();
+    fn test_parse_with_policy() {
+        let mut grok = Grok::default();
+        grok.add_pattern(
+            "NGINX_HOST",
+            r"(?:%{IP:ip}|%{NGINX_NOTSEPARATOR:domain})(:%{NUMBER:port:int})?",
+        );
+        grok.add_pattern("NGINX_NOTSEPARATOR", r"[^\t ,:]+");
+        let pattern = grok.compile("%{NGINX_HOST}", true).unwrap();
+        let policy = HashMap::from([
+            ("port", MissingPolicy::Default(Value::Int(80))),
+            ("domain", MissingPolicy::Omit),
+            ("ip", MissingPolicy::Null),
+            ("unknown", MissingPolicy::Null),
+        ]);
 
-        {
-            let grok = Grok::from_iter([("NAME", r"[A-z0-9._-]+")]);
-            let pattern = grok.compile("%{NAME}", false).unwrap();
-            assert_eq!(expected, pattern.parse("admin").unwrap());
-        }
-        {
-            let grok = Grok::from([("NAME", r"[A-z0-9._-]+")]);
-            let pattern = grok.compile("%{NAME}", false).unwrap();
-            assert_eq!(expected, pattern.parse("admin").unwrap());
-        }
+        assert_eq!(
+            HashMap::from([
+                ("ip".to_string(), Value::String("127.0.0.1".to_string())),
+                ("port".to_string(), Value::Int(80)),
+            ]),
+            pattern.parse_with_policy("127.0.0.1", &policy).unwrap()
+        );
+        assert_eq!(
+            HashMap::from([
+                ("ip".to_string(), Value::Null),
+                (
+                    "domain".to_string(),
+                    Value::String("example.com".to_string())
+                ),
+                ("port".to_string(), Value::Int(8080)),
+            ]),
+            pattern
+                .parse_with_policy("example.com:8080", &policy)
+                .unwrap()
+        );
+        assert!(pattern.parse_with_policy("", &policy).unwrap().is_empty());
+
+        // nothing is captured, but the input is matched
+        let pattern = grok.compile("GET(?: %{INT:port:int})?", true).unwrap();
+        assert_eq!(
+            HashMap::from([("port".to_string(), Value::Int(80))]),
+            pattern.parse_with_policy("GET", &policy).unwrap()
+        );
+        assert!(pattern
+            .parse_with_policy("PUT", &policy)
+            .unwrap()
+            .is_empty());
+
+        let port: Option<i64> = FromValue::from_value(&Value::Null).unwrap();
+        assert_eq!(None, port);
     }
 
     #[test]
-    fn test_pattern_parse_no_captures() {
-        let grok = Grok::default();
-        let pattern = grok.compile("%{USERNAME}", false).unwrap();
+    fn test_recursion_limit() {
+        // the self-referencing pattern used to be replaced forever
+        let grok = Grok::from([("A", "x%{A}")]);
+        assert_eq!(
+            Err(GrokError::RecursionLimit(MAX_RECURSION)),
+            grok.compile("%{A}", false).map(|_| ())
+        );
+        assert_eq!(
+            Err(GrokError::RecursionLimit(MAX_RECURSION)),
+            grok.compile("%{A:a}", true).map(|_| ())
+        );
+    }
 
-        assert!(pattern.parse("$#@").unwrap().is_empty());
-        assert!(pattern.parse("").unwrap().is_empty());
-        assert!(pattern.parse("✅🚀🌍").unwrap().is_empty());
-        assert!(pattern.parse("     ").unwrap().is_empty());
+    #[test]
+    fn test_recursion_cycle() {
+        // A -> B -> A is stopped by the max rounds of the expansion
+        let grok = Grok::from([("A", "%{B}"), ("B", "(%{A})")]);
+        assert_eq!(
+            Err(GrokError::RecursionLimit(MAX_RECURSION)),
+            grok.compile("%{A}", false).map(|_| ())
+        );
+
+        let grok = Grok::from([("A", "a%{B:b}"), ("B", "b%{A:a}")]);
+        assert_eq!(
+            Err(GrokError::RecursionLimit(MAX_RECURSION)),
+            grok.compile("%{A:a}", true).map(|_| ())
+        );
     }
 
     #[test]
-    fn test_composite_or_pattern() {
+    fn test_description() {
         let mut grok = Grok::default();
-        grok.add_pattern("MAC", r"(?:%{CISCOMAC}|%{WINDOWSMAC}|%{COMMONMAC})");
-        grok.add_pattern("CISCOMAC", r"(?:(?:[A-Fa-f0-9]{4}\.){2}[A-Fa-f0-9]{4})");
-        grok.add_pattern("WINDOWSMAC", r"(?:(?:[A-Fa-f0-9]{2}-){5}[A-Fa-f0-9]{2})");
-        grok.add_pattern("COMMONMAC", r"(?:(?:[A-Fa-f0-9]{2}:){5}[A-Fa-f0-9]{2})");
+        grok.add_pattern("SIZE", r#"%{NUMBER:bytes:int:"response size"}"#);
+        let pattern = grok
+            .compile(
+                r#"%{WORD:method:"the HTTP method"} %{SIZE} %{WORD:status}"#,
+                true,
+            )
+            .unwrap();
 
-        let pattern = grok.compile("%{MAC}", false).unwrap();
-        let expected = [
-            ("MAC", "5E:FF:56:A2:AF:15"),
-            ("COMMONMAC", "5E:FF:56:A2:AF:15"),
-        ]
-        .into_iter()
-        .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
-        .collect::<HashMap<String, Value>>();
+        assert_eq!(
+            vec![
+                FieldSchema {
+                    name: "method".to_string(),
+                    type_: None,
+                    description: Some("the HTTP method".to_string()),
+                },
+                FieldSchema {
+                    name: "bytes".to_string(),
+                    type_: Some("int".to_string()),
+                    description: Some("response size".to_string()),
+                },
+                FieldSchema {
+                    name: "status".to_string(),
+                    type_: None,
+                    description: None,
+                },
+            ],
+            pattern.schema()
+        );
+        assert_eq!(
+            HashMap::from([
+                ("method".to_string(), Value::String("GET".to_string())),
+                ("bytes".to_string(), Value::Int(512)),
+                ("status".to_string(), Value::String("ok".to_string())),
+            ]),
+            pattern.parse("GET 512 ok").unwrap()
+        );
 
-        assert_eq!(expected, pattern.parse("5E:FF:56:A2:AF:15").unwrap());
+        let pattern = grok.compile(r#"%{WORD:w::""}"#, true);
+        assert!(matches!(
+            pattern,
+            Err(GrokError::MalformedPlaceholder { .. })
+        ));
+    }
+
+    #[test]
+    fn test_format_table() {
+        let map = HashMap::from([
+            ("method".to_string(), Value::String("GET".to_string())),
+            ("status".to_string(), Value::Int(200)),
+            (
+                "url.original".to_string(),
+                Value::String("/index".to_string()),
+            ),
+            (
+                "tags".to_string(),
+                Value::Array(vec![Value::Bool(true), Value::Float(0.5), Value::Null]),
+            ),
+            ("raw".to_string(), Value::Bytes(b"a\xff".to_vec())),
+        ]);
         assert_eq!(
-            expected,
-            pattern.parse("127.0.0.1 5E:FF:56:A2:AF:15").unwrap()
+            "\
+field         value
+method        GET
+raw           a\\xff
+status        200
+tags          [true, 0.5, null]
+url.original  /index
+",
+            format_table(&map)
         );
+        assert_eq!("field  value\n", format_table(&HashMap::new()));
     }
 
     #[test]
-    fn test_multiple_patterns() {
+    fn test_require() {
         let mut grok = Grok::default();
-        grok.add_pattern("YEAR", r"(\d\d){1,2}");
-        grok.add_pattern("MONTH", r"\b(?:Jan(?:uary)?|Feb(?:ruary)?|Mar(?:ch)?|Apr(?:il)?|May|Jun(?:e)?|Jul(?:y)?|Aug(?:ust)?|Sep(?:tember)?|Oct(?:ober)?|Nov(?:ember)?|Dec(?:ember)?)\b");
-        grok.add_pattern("DAY", r"(?:Mon(?:day)?|Tue(?:sday)?|Wed(?:nesday)?|Thu(?:rsday)?|Fri(?:day)?|Sat(?:urday)?|Sun(?:day)?)");
-        let pattern = grok.compile("%{DAY} %{MONTH} %{YEAR}", false).unwrap();
+        assert_eq!(
+            Err(GrokError::PatternNotFound("NOPE".to_string())),
+            grok.require(&["IP", "USERNAME", "NOPE"])
+        );
+        assert_eq!(
+            Err(GrokError::PatternNotFound("NOPE, NGINX_HOST".to_string())),
+            grok.require(&["NOPE", "IP", "NGINX_HOST"])
+        );
 
-        let expected = [("DAY", "Monday"), ("MONTH", "March"), ("YEAR", "2012")]
-            .into_iter()
-            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
-            .collect::<HashMap<String, Value>>();
-        assert_eq!(expected, pattern.parse("Monday March 2012").unwrap());
+        grok.add_pattern("NOPE", "nope");
+        assert_eq!(Ok(()), grok.require(&["IP", "USERNAME", "NOPE"]));
+        assert_eq!(Ok(()), grok.require(&[]));
     }
 
     #[test]
-    fn test_adhoc_pattern() {
+    fn test_hex_type() {
         let grok = Grok::default();
-        let pattern = grok.compile(r"\[(?<threadname>[^\]]+)\]", false).unwrap();
-        let expected = [("threadname", "thread1")]
-            .into_iter()
-            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())))
-            .collect::<HashMap<String, Value>>();
-        assert_eq!(expected, pattern.parse("[thread1]").unwrap());
+        let pattern = grok
+            .compile("sha256=%{BASE16NUM:hash:hex(64)}", true)
+            .unwrap();
+        let hash = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        assert_eq!(
+            Some(&Value::String(hash.to_ascii_lowercase())),
+            pattern
+                .parse(&format!("sha256={hash}"))
+                .unwrap()
+                .get("hash")
+        );
+        assert_eq!(
+            Err(GrokError::Conversion {
+                value: "d41d8cd98f00b204e9800998ecf8427e".to_string(),
+                reason: "expected 64 hex digits".to_string(),
+            }),
+            pattern.parse("sha256=d41d8cd98f00b204e9800998ecf8427e")
+        );
+
+        let pattern = grok.compile("%{NOTSPACE:hash:hex}", true).unwrap();
+        assert_eq!(
+            Some(&Value::String("abc123".to_string())),
+            pattern.parse("ABC123").unwrap().get("hash")
+        );
+        assert!(pattern.parse("xyz").is_err());
+
+        assert_eq!(
+            Err(GrokError::UnknownType("hex(0)".to_string())),
+            grok.compile("%{NOTSPACE:hash:hex(0)}", true).map(|_| ())
+        );
     }
 
     #[test]
-    fn test_type() {
+    fn test_parse_chain() {
+        let grok = Grok::default();
+        let outer = grok
+            .compile(
+                "%{TIMESTAMP_ISO8601:ts} %{LOGLEVEL:level} %{GREEDYDATA:msg}",
+                true,
+            )
+            .unwrap();
+        let inner = grok
+            .compile(
+                "%{WORD:action} from %{IP:client} took %{INT:ms:int}ms(: %{GREEDYDATA:msg})?",
+                true,
+            )
+            .unwrap();
+        let detail = grok.compile("code=%{INT:code:int}", true).unwrap();
+        let line = "2024-06-05T10:00:00Z WARN login from 10.0.0.1 took 250ms: code=401";
+
+        assert_eq!(
+            HashMap::from([
+                (
+                    "ts".to_string(),
+                    Value::String("2024-06-05T10:00:00Z".into())
+                ),
+                ("level".to_string(), Value::String("WARN".into())),
+                ("msg".to_string(), Value::String("code=401".into())),
+                ("action".to_string(), Value::String("login".into())),
+                ("client".to_string(), Value::String("10.0.0.1".into())),
+                ("ms".to_string(), Value::Int(250)),
+                ("code".to_string(), Value::Int(401)),
+            ]),
+            parse_chain(&[&outer, &inner, &detail], line, "msg").unwrap()
+        );
+
+        // the inner pattern doesn't match, so the outer result is kept
+        let line = "2024-06-05T10:00:00Z INFO started";
+        assert_eq!(
+            outer.parse(line).unwrap(),
+            parse_chain(&[&outer, &inner, &detail], line, "msg").unwrap()
+        );
+        assert!(parse_chain(&[&outer, &inner], "nothing", "msg")
+            .unwrap()
+            .is_empty());
+        assert!(parse_chain(&[], line, "msg").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_match_failure() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile("^%{IP:client} %{WORD:method} %{INT:status:int}$", true)
+            .unwrap();
+
+        assert_eq!(None, pattern.match_failure("10.0.0.1 GET 200"));
+        // the status is not a number
+        assert_eq!(Some(13), pattern.match_failure("10.0.0.1 GET xyz"));
+        // the trailing text after the status
+        assert_eq!(Some(16), pattern.match_failure("10.0.0.1 GET 200 ms"));
+        // `200` is taken as the method, then the input ends before the status
+        assert_eq!(Some(12), pattern.match_failure("10.0.0.1 200"));
+        assert_eq!(Some(9), pattern.match_failure("10.0.0.1 - 200"));
+        assert_eq!(Some(0), pattern.match_failure(""));
+
+        let pattern = grok.compile("%{WORD:w} world", true).unwrap();
+        assert_eq!(Some(7), pattern.match_failure("hello wörld"));
+    }
+
+    #[test]
+    fn test_compile_with_defaults() {
         let mut grok = Grok::default();
-        grok.add_pattern("NUMBER", r"\d+");
+        grok.add_pattern(
+            "NGINX_HOST",
+            r"(?:%{IP:destination.ip}|%{NGINX_NOTSEPARATOR:destination.domain})(:%{NUMBER:destination.port:int})?",
+        );
+        grok.add_pattern("NGINX_NOTSEPARATOR", r"[^\t ,:]+");
+        let defaults = HashMap::from([
+            ("destination.ip".to_string(), Value::Null),
+            (
+                "destination.domain".to_string(),
+                Value::String("-".to_string()),
+            ),
+        ]);
+        let pattern = grok
+            .compile_with_defaults("%{NGINX_HOST}", true, defaults)
+            .unwrap();
 
-        // int
-        {
-            let pattern = grok.compile("%{NUMBER:digit:int}", false).unwrap();
-            let expected = [("digit", Value::Int(123))]
-                .into_iter()
-                .map(|(k, v)| (k.to_string(), v))
-                .collect::<HashMap<String, Value>>();
-            assert_eq!(expected, pattern.parse("hello 123").unwrap());
-        }
+        // the domain branch is taken
+        assert_eq!(
+            HashMap::from([
+                ("destination.ip".to_string(), Value::Null),
+                (
+                    "destination.domain".to_string(),
+                    Value::String("example.com".to_string())
+                ),
+                ("destination.port".to_string(), Value::Int(80)),
+            ]),
+            pattern.parse("example.com:80").unwrap()
+        );
+        // the ip branch is taken
+        assert_eq!(
+            HashMap::from([
+                (
+                    "destination.ip".to_string(),
+                    Value::String("127.0.0.1".to_string())
+                ),
+                (
+                    "destination.domain".to_string(),
+                    Value::String("-".to_string())
+                ),
+            ]),
+            pattern.parse("127.0.0.1").unwrap()
+        );
+        // the defaults are not applied if the input is not matched
+        assert!(pattern.parse("").unwrap().is_empty());
 
-        // float
-        {
-            let pattern = grok.compile("%{NUMBER:digit:float}", false).unwrap();
-            let expected = [("digit", Value::Float(123.0))]
-                .into_iter()
-                .map(|(k, v)| (k.to_string(), v))
-                .collect::<HashMap<String, Value>>();
-            assert_eq!(expected, pattern.parse("hello 123.0").unwrap());
-        }
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .default_value("source", Value::String("nginx".to_string()));
+        let pattern = grok.compile_with_opts("%{NGINX_HOST}", &opts).unwrap();
+        assert_eq!(
+            Some(&Value::String("nginx".to_string())),
+            pattern.parse("127.0.0.1").unwrap().get("source")
+        );
+        assert_eq!("source", pattern.schema()[3].name);
+    }
 
-        // wrong type
-        {
-            let pattern = grok.compile("%{NUMBER:digit:wrong}", false);
-            assert!(pattern.is_err());
-        }
+    #[test]
+    fn test_compile_iterations() {
+        let grok = Grok::default();
+        let opts = CompileOptions::default();
 
-        {
-            // wrong value
-            let pattern = grok.compile("%{USERNAME:digit:float}", false).unwrap();
-            assert_eq!(
-                Err("invalid float literal: grok".to_string()),
-                pattern.parse("grok")
-            );
-        }
+        let simple = grok.compile_detailed("%{WORD:w}", &opts).unwrap();
+        assert_eq!(1, simple.iterations);
+        assert_eq!(
+            2,
+            grok.compile_detailed("%{WORD:a} %{WORD:b}", &opts)
+                .unwrap()
+                .iterations
+        );
+
+        let nested = grok
+            .compile_detailed("%{HTTPD_COMBINEDLOG}", &opts)
+            .unwrap();
+        assert!(nested.iterations > 20, "{}", nested.iterations);
+        assert!(nested.iterations < MAX_RECURSION as usize);
+    }
+
+    #[test]
+    fn test_infer_types_by_name() {
+        let grok = Grok::default();
+        let expr = "%{IP:dest.ip}:%{NUMBER:dest.port} %{NUMBER:bytes}";
+        let input = "0:0::1:8080 512";
+
+        let pattern = grok.compile(expr, true).unwrap();
+        assert_eq!(
+            Some(&Value::String("8080".to_string())),
+            pattern.parse(input).unwrap().get("dest.port")
+        );
+
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .infer_types_by_name(true);
+        let pattern = grok.compile_with_opts(expr, &opts).unwrap();
+        assert_eq!(
+            HashMap::from([
+                ("dest.ip".to_string(), Value::String("::1".to_string())),
+                ("dest.port".to_string(), Value::Int(8080)),
+                // the suffix needs the separator
+                ("bytes".to_string(), Value::String("512".to_string())),
+            ]),
+            pattern.parse(input).unwrap()
+        );
+
+        // the explicit type and the custom mapping take precedence
+        let opts = opts.infer_type("bytes", "int");
+        let pattern = grok
+            .compile_with_opts("%{NUMBER:dest.port:float} %{NUMBER:bytes}", &opts)
+            .unwrap();
+        assert_eq!(
+            HashMap::from([
+                ("dest.port".to_string(), Value::Float(8080.0)),
+                ("bytes".to_string(), Value::Int(512)),
+            ]),
+            pattern.parse("8080 512").unwrap()
+        );
+
+        // the value which can't be converted to the inferred type is kept as string
+        let pattern = grok
+            .compile_with_opts("%{IPORHOST:source.ip} %{NUMBER:bytes:int}", &opts)
+            .unwrap();
+        assert_eq!(
+            HashMap::from([
+                (
+                    "source.ip".to_string(),
+                    Value::String("example.com".to_string())
+                ),
+                ("bytes".to_string(), Value::Int(512)),
+            ]),
+            pattern.parse("example.com 512").unwrap()
+        );
+        let (regex, alias) = grok.expand("%{IPORHOST:source.ip}", &opts).unwrap();
+        let pattern = Pattern::compile_expanded(&regex, alias).unwrap();
+        assert!(matches!(
+            pattern.parse("example.com"),
+            Err(GrokError::Conversion { .. })
+        ));
+        let pattern = grok
+            .compile_with_opts("%{IPORHOST:source.ip:ip}", &opts)
+            .unwrap();
+        assert!(matches!(
+            pattern.parse("example.com"),
+            Err(GrokError::Conversion { .. })
+        ));
     }
 
     #[test]
@@ -0,0 +1,130 @@
+use std::{collections::HashMap, io::BufRead};
+
+use crate::{GrokError, Pattern, Value};
+
+/// The result of [`Pattern::parse_lines_report`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LinesReport {
+    /// the results of the parsed lines, in the order of the input.
+    pub records: Vec<HashMap<String, Value>>,
+    /// the 1-based line number and the error of each failed line, e.g. [`GrokError::NoMatch`].
+    pub failures: Vec<(usize, GrokError)>,
+}
+
+impl Pattern {
+    /// parse each line of the reader, the unmatched line is [`GrokError::NoMatch`],
+    /// and the failure of reading is [`GrokError::Io`], which ends the iteration.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("%{WORD:method} %{INT:status:int}", true).unwrap();
+    /// let results = pattern.parse_lines("GET 200\nPOST 201\n".as_bytes()).collect::<Vec<_>>();
+    /// assert_eq!(2, results.len());
+    /// ```
+    pub fn parse_lines<'a, R: BufRead + 'a>(
+        &'a self,
+        reader: R,
+    ) -> impl Iterator<Item = Result<HashMap<String, Value>, GrokError>> + 'a {
+        let mut failed = false;
+        reader.lines().map_while(move |line| {
+            if failed {
+                return None;
+            }
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    failed = true;
+                    return Some(Err(GrokError::Io(e.to_string())));
+                }
+            };
+            Some(match self.regex.captures(&line) {
+                Some(caps) => self.extract(&line, &caps),
+                None => Err(GrokError::NoMatch),
+            })
+        })
+    }
+
+    /// parse each line of the reader like [`Pattern::parse_lines`], and collect the results
+    /// and the failures with the 1-based line numbers, e.g. for the data quality dashboards.
+    /// the failure of reading is returned as an error, since the rest of the lines can't be read.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let pattern = grok.compile("^%{WORD:method} %{INT:status:int}$", true).unwrap();
+    /// let report = pattern.parse_lines_report("GET 200\noops\n".as_bytes()).unwrap();
+    /// assert_eq!(1, report.records.len());
+    /// assert_eq!(2, report.failures[0].0);
+    /// ```
+    pub fn parse_lines_report<R: BufRead>(&self, reader: R) -> Result<LinesReport, GrokError> {
+        let mut report = LinesReport::default();
+
+        for (i, result) in self.parse_lines(reader).enumerate() {
+            match result {
+                Ok(record) => report.records.push(record),
+                Err(GrokError::Io(e)) => return Err(GrokError::Io(e)),
+                Err(e) => report.failures.push((i + 1, e)),
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Grok;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_lines_report() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile("^%{WORD:method} %{NOTSPACE:status:int}$", true)
+            .unwrap();
+        let input = "GET 200\nnot matched at all\nPOST 201\n\nDELETE 204";
+
+        let report = pattern.parse_lines_report(input.as_bytes()).unwrap();
+        assert_eq!(3, report.records.len());
+        assert_eq!(Some(&Value::Int(204)), report.records[2].get("status"));
+        assert_eq!(
+            vec![(2, GrokError::NoMatch), (4, GrokError::NoMatch)],
+            report.failures
+        );
+
+        let report = pattern
+            .parse_lines_report("GET 200\nPUT abc".as_bytes())
+            .unwrap();
+        assert_eq!(
+            vec![(
+                2,
+                GrokError::Conversion {
+                    value: "abc".to_string(),
+                    reason: "invalid digit found in string".to_string(),
+                }
+            )],
+            report.failures
+        );
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let grok = Grok::default();
+        // the pattern without any named group matches with an empty result
+        let pattern = grok.compile("^%{WORD}$", true).unwrap();
+        let results = pattern
+            .parse_lines("GET\n\n".as_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Ok(HashMap::new()), Err(GrokError::NoMatch)], results);
+
+        let pattern = grok.compile("%{WORD:w}", true).unwrap();
+        let report = pattern.parse_lines_report(&b"GET\n\xff\nPUT"[..]);
+        assert!(matches!(report, Err(GrokError::Io(_))));
+    }
+}
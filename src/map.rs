@@ -0,0 +1,510 @@
+use std::{collections::HashMap, sync::Arc};
+
+use regex::Captures;
+use regex_automata::{meta::Regex, Anchored, Input};
+
+use crate::{convert, group_names, AliasType, ArrayOverflow, GrokError, Value};
+
+/// the field of the map built by the bare `@key` and `@value` markers.
+const DEFAULT_FIELD: &str = "kv";
+
+/// The pairs captured by the `@key` and `@value` placeholders, e.g. `(?:%{WORD:@key}=%{DATA:@value}\s*)+`,
+/// collected into a [`Value::Map`] in the order of the input.
+#[derive(Debug)]
+pub(crate) struct MapGroup {
+    /// the field of the map, e.g. `labels` of `%{WORD:labels@key}`, or `kv` of the bare `@key`.
+    pub(crate) field: Arc<str>,
+    key: String,
+    value: String,
+    value_type: Option<String>,
+    /// the group wrapping the whole repetition, and the regex of an iteration as the group 1,
+    /// followed by the next iteration or the end of the repetition.
+    repetition: Option<(String, Regex)>,
+}
+
+impl MapGroup {
+    /// the capture group is a part of the map, so it's not a field of the result.
+    pub(crate) fn owns(&self, group: &str) -> bool {
+        self.key == group
+            || self.value == group
+            || self
+                .repetition
+                .as_ref()
+                .is_some_and(|(name, _)| name == group)
+    }
+
+    /// the pairs of every iteration of the repetition, or the single pair if the markers are not repeated.
+    /// at most `max` pairs are collected, the others are handled by `overflow`.
+    pub(crate) fn extract(
+        &self,
+        s: &str,
+        caps: &Captures<'_>,
+        max: Option<usize>,
+        overflow: ArrayOverflow,
+    ) -> Result<Option<Value>, GrokError> {
+        let mut pairs = Vec::new();
+
+        match &self.repetition {
+            Some((name, iterations)) => {
+                if let Some(m) = caps.name(name) {
+                    // the haystack ends with the repetition, and keeps the text before it for `\b` and `^`.
+                    // every iteration is anchored, and must be followed by the next one or the end,
+                    // so a lazy value, e.g. `DATA`, still extends to the next iteration,
+                    // and the repetition is split in a single pass.
+                    let haystack = &s[..m.end()];
+                    let mut iteration = iterations.create_captures();
+                    let mut pos = m.start();
+                    while pos < m.end() {
+                        let input = Input::new(haystack).range(pos..).anchored(Anchored::Yes);
+                        iterations.search_captures(&input, &mut iteration);
+                        let Some(end) = iteration.get_group(1).map(|span| span.end) else {
+                            break;
+                        };
+                        let key = iteration.get_group_by_name(&self.key);
+                        let value = iteration.get_group_by_name(&self.value);
+                        if let (Some(key), Some(value)) = (key, value) {
+                            let pair = (&haystack[key.range()], &haystack[value.range()]);
+                            if !self.push(pair, &mut pairs, max, overflow)? {
+                                break;
+                            }
+                        }
+                        if end <= pos {
+                            break;
+                        }
+                        pos = end;
+                    }
+                }
+            }
+            None => {
+                if let (Some(key), Some(value)) = (caps.name(&self.key), caps.name(&self.value)) {
+                    self.push((key.as_str(), value.as_str()), &mut pairs, max, overflow)?;
+                }
+            }
+        }
+
+        Ok((!pairs.is_empty()).then_some(Value::Map(pairs)))
+    }
+
+    /// push the pair if there are less than `max` pairs, return false if the others should be dropped.
+    fn push(
+        &self,
+        (key, value): (&str, &str),
+        pairs: &mut Vec<(String, Value)>,
+        max: Option<usize>,
+        overflow: ArrayOverflow,
+    ) -> Result<bool, GrokError> {
+        match max {
+            Some(max) if pairs.len() >= max => match overflow {
+                ArrayOverflow::Error => Err(GrokError::ArrayTooLong {
+                    field: self.field.to_string(),
+                    max,
+                }),
+                ArrayOverflow::Truncate => Ok(false),
+            },
+            _ => {
+                let value = convert(value, self.value_type.as_deref())?;
+                pairs.push((key.to_string(), value));
+                Ok(true)
+            }
+        }
+    }
+}
+
+/// find the pairs of the `@key` and `@value` markers, and wrap the repetition enclosing each pair
+/// in a capture group, so the iterations can be parsed one by one.
+/// the marker without its counterpart is kept as a plain field.
+pub(crate) fn prepare_maps(
+    regex: &mut String,
+    alias: &HashMap<String, AliasType>,
+) -> Result<Vec<MapGroup>, GrokError> {
+    let mut maps = Vec::new();
+    for (field, key, value) in markers(alias) {
+        let name = unused_group_name(regex);
+        let repetition = match wrap_repetition(regex, key, value, &name) {
+            Some((name, body)) => {
+                let iterations = format!(
+                    r"{}({body})(?:{}|\z)",
+                    leading_flags(regex),
+                    strip_group_names(&body)
+                );
+                let iterations =
+                    Regex::new(&iterations).map_err(|e| GrokError::Regex(e.to_string()))?;
+                Some((name, iterations))
+            }
+            None => None,
+        };
+
+        maps.push(MapGroup {
+            field: Arc::from(field),
+            key: key.to_string(),
+            value: value.to_string(),
+            value_type: alias[value].1.clone(),
+            repetition,
+        });
+    }
+
+    Ok(maps)
+}
+
+/// the fields of the paired `@key` and `@value` markers with the groups of the key and the value,
+/// sorted by the field.
+pub(crate) fn markers(alias: &HashMap<String, AliasType>) -> Vec<(&str, &str, &str)> {
+    let mut markers: HashMap<&str, (Option<&str>, Option<&str>)> = HashMap::new();
+    let mut groups = alias.iter().collect::<Vec<_>>();
+    // the first placeholder wins, e.g. in the alternations
+    groups.sort_by_key(|(group, _)| group_index(group));
+
+    for (group, (name, _)) in groups {
+        if let Some(field) = name.strip_suffix("@key") {
+            markers.entry(field).or_default().0.get_or_insert(group);
+        } else if let Some(field) = name.strip_suffix("@value") {
+            markers.entry(field).or_default().1.get_or_insert(group);
+        }
+    }
+
+    let mut markers = markers
+        .into_iter()
+        .filter_map(|(field, pair)| match pair {
+            (Some(key), Some(value)) if field.is_empty() => Some((DEFAULT_FIELD, key, value)),
+            (Some(key), Some(value)) => Some((field, key, value)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    markers.sort();
+    markers
+}
+
+/// the name of the group wrapping the repetition, e.g. `map0`, which is not used by the regex yet.
+fn unused_group_name(regex: &str) -> String {
+    let names = group_names(regex);
+    (0..)
+        .map(|i| format!("map{i}"))
+        .find(|name| !names.contains(&name.as_str()))
+        .unwrap_or_default()
+}
+
+/// turn the named groups into the non-capturing ones, so the body can be repeated in a regex.
+fn strip_group_names(body: &str) -> String {
+    let mut opens = group_bounds(body)
+        .into_iter()
+        .map(|(open, _)| open)
+        .collect::<Vec<_>>();
+    // from the last one, so the offsets of the former ones are kept
+    opens.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut stripped = body.to_string();
+    for open in opens {
+        let rest = &body[open + 1..];
+        let prefix = ["?<", "?P<"].into_iter().find(|p| rest.starts_with(p));
+        if let (Some(prefix), Some(end)) = (prefix, rest.find('>')) {
+            if !rest[prefix.len()..].starts_with(['=', '!']) {
+                stripped.replace_range(open + 1..open + 2 + end, "?:");
+            }
+        }
+    }
+    stripped
+}
+
+/// the index of the generated group name, e.g. 3 of `name3`.
+fn group_index(group: &str) -> usize {
+    group
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .parse()
+        .unwrap_or(usize::MAX)
+}
+
+/// the inline flags at the start of the expanded regex, e.g. `(?i)`, which apply to the iterations as well.
+fn leading_flags(regex: &str) -> &str {
+    match regex.find(')') {
+        Some(end)
+            if regex.starts_with("(?")
+                && regex[2..end]
+                    .chars()
+                    .all(|c| c.is_ascii_alphabetic() || c == '-') =>
+        {
+            &regex[..=end]
+        }
+        _ => "",
+    }
+}
+
+/// wrap the innermost repeated group which encloses both groups in a capture group named `name`,
+/// and return the name and the regex of the repeated group, i.e. a single iteration.
+fn wrap_repetition(
+    regex: &mut String,
+    key: &str,
+    value: &str,
+    name: &str,
+) -> Option<(String, String)> {
+    let key = regex.find(&format!("(?<{key}>"))?;
+    let value = regex.find(&format!("(?<{value}>"))?;
+    let bytes = regex.as_bytes();
+
+    let (open, close) = group_bounds(regex)
+        .into_iter()
+        .filter(|(open, close)| *open < key.min(value) && *close > key.max(value))
+        .filter(|(_, close)| matches!(bytes.get(close + 1), Some(b'+' | b'*' | b'{')))
+        .max_by_key(|(open, _)| *open)?;
+
+    let mut end = match bytes[close + 1] {
+        b'{' => close + 1 + regex[close + 1..].find('}')? + 1,
+        _ => close + 2,
+    };
+    if bytes.get(end) == Some(&b'?') {
+        end += 1;
+    }
+
+    let body = regex[open..=close].to_string();
+    regex.insert(end, ')');
+    regex.insert_str(open, &format!("(?<{name}>"));
+    Some((name.to_string(), body))
+}
+
+/// the byte offsets of the open and the close parentheses of every group, the escaped parentheses
+/// and the ones in a character class are skipped.
+fn group_bounds(regex: &str) -> Vec<(usize, usize)> {
+    let bytes = regex.as_bytes();
+    let mut bounds = Vec::new();
+    let mut opens = Vec::new();
+    let mut in_class = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => in_class = true,
+            b']' => in_class = false,
+            b'(' if !in_class => opens.push(i),
+            b')' if !in_class => {
+                if let Some(open) = opens.pop() {
+                    bounds.push((open, i));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    bounds
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CompileOptions, Grok, Pattern};
+
+    use super::*;
+
+    fn string(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[test]
+    fn test_map() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile(r"(?:%{WORD:@key}=%{NOTSPACE:@value}\s*)+", true)
+            .unwrap();
+        assert_eq!(
+            HashMap::from([(
+                "kv".to_string(),
+                Value::Map(vec![
+                    ("a".to_string(), string("1")),
+                    ("b".to_string(), string("2")),
+                ])
+            )]),
+            pattern.parse("a=1 b=2").unwrap()
+        );
+
+        let pattern = grok
+            .compile(
+                r"^%{WORD:method} (?:%{WORD:labels@key}:%{INT:labels@value:int},?)* %{INT:status:int}$",
+                true,
+            )
+            .unwrap();
+        assert_eq!(
+            HashMap::from([
+                ("method".to_string(), string("GET")),
+                (
+                    "labels".to_string(),
+                    Value::Map(vec![
+                        ("z".to_string(), Value::Int(3)),
+                        ("a".to_string(), Value::Int(1)),
+                        ("z".to_string(), Value::Int(2)),
+                    ])
+                ),
+                ("status".to_string(), Value::Int(200)),
+            ]),
+            pattern.parse("GET z:3,a:1,z:2 200").unwrap()
+        );
+        // no iteration, so no map
+        assert_eq!(
+            HashMap::from([
+                ("method".to_string(), string("GET")),
+                ("status".to_string(), Value::Int(200)),
+            ]),
+            pattern.parse("GET  200").unwrap()
+        );
+        let names = pattern
+            .schema()
+            .into_iter()
+            .map(|f| f.name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["method", "status", "labels"], names);
+    }
+
+    #[test]
+    fn test_map_lazy_value() {
+        let grok = Grok::default();
+        // DATA is lazy, so the unanchored repetition stops after `a=`
+        let pattern = grok
+            .compile(r"(?:%{WORD:@key}=%{DATA:@value}\s*)+", true)
+            .unwrap();
+        assert_eq!(
+            Some(&Value::Map(vec![("a".to_string(), string(""))])),
+            pattern.parse("a=1 b=2").unwrap().get("kv")
+        );
+
+        // the iterations are split in the context of the whole match, so the values extend
+        let pattern = grok
+            .compile(r"^(?:%{WORD:@key}=%{DATA:@value}\s*)+$", true)
+            .unwrap();
+        assert_eq!(
+            Some(&Value::Map(vec![
+                ("a".to_string(), string("1")),
+                ("b".to_string(), string("2 c")),
+                ("d".to_string(), string("3")),
+            ])),
+            pattern.parse("a=1 b=2 c d=3").unwrap().get("kv")
+        );
+    }
+
+    #[test]
+    fn test_map_many_pairs() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile(r"^(?:%{WORD:@key}=%{NOTSPACE:@value}\s*)+$", true)
+            .unwrap();
+        let input = (0..20_000)
+            .map(|i| format!("k{i}=v{i}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // the iterations are split in a single pass, the quadratic one takes minutes
+        let result = pattern.parse(&input).unwrap();
+        let Some(Value::Map(pairs)) = result.get("kv") else {
+            panic!("{result:?}");
+        };
+        assert_eq!(20_000, pairs.len());
+        assert_eq!(("k19999".to_string(), string("v19999")), pairs[19_999]);
+    }
+
+    #[test]
+    fn test_map_max_len() {
+        let grok = Grok::default();
+        let expr = r"(?:%{WORD:@key}=%{NOTSPACE:@value}\s*)+";
+        let opts = CompileOptions::default()
+            .named_capture_only(true)
+            .max_array_len(2, ArrayOverflow::Error);
+
+        let pattern = grok.compile_with_opts(expr, &opts).unwrap();
+        assert_eq!(
+            Err(GrokError::ArrayTooLong {
+                field: "kv".to_string(),
+                max: 2
+            }),
+            pattern.parse("a=1 b=2 c=3 d=4")
+        );
+        assert!(pattern.parse("a=1 b=2").is_ok());
+
+        let opts = opts.max_array_len(2, ArrayOverflow::Truncate);
+        let pattern = grok.compile_with_opts(expr, &opts).unwrap();
+        assert_eq!(
+            Some(&Value::Map(vec![
+                ("a".to_string(), string("1")),
+                ("b".to_string(), string("2")),
+            ])),
+            pattern.parse("a=1 b=2 c=3 d=4").unwrap().get("kv")
+        );
+    }
+
+    #[test]
+    fn test_map_compile_expanded() {
+        let grok = Grok::default();
+        let opts = CompileOptions::default().named_capture_only(true);
+        let (regex, alias) = grok
+            .expand(r"(?:%{WORD:@key}=%{NOTSPACE:@value}\s*)+", &opts)
+            .unwrap();
+        let pattern = Pattern::compile_expanded(&regex, alias).unwrap();
+        assert_eq!(
+            HashMap::from([(
+                "kv".to_string(),
+                Value::Map(vec![
+                    ("a".to_string(), string("1")),
+                    ("b".to_string(), string("2")),
+                ])
+            )]),
+            pattern.parse("a=1 b=2").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_map_group_name() {
+        let grok = Grok::default();
+        let pattern = grok
+            .compile(r"(?<map0>x) (?:%{WORD:@key}=%{WORD:@value} ?)+", true)
+            .unwrap();
+        assert_eq!(
+            HashMap::from([
+                ("map0".to_string(), string("x")),
+                (
+                    "kv".to_string(),
+                    Value::Map(vec![
+                        ("a".to_string(), string("1")),
+                        ("b".to_string(), string("2")),
+                    ])
+                ),
+            ]),
+            pattern.parse("x a=1 b=2").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_map_without_repetition() {
+        let grok = Grok::default();
+        let pattern = grok.compile(r"%{WORD:@key}=%{WORD:@value}", true).unwrap();
+        assert_eq!(
+            Some(&Value::Map(vec![("a".to_string(), string("b"))])),
+            pattern.parse("a=b").unwrap().get("kv")
+        );
+
+        // the unpaired marker is a plain field
+        let pattern = grok.compile(r"%{WORD:@key}", true).unwrap();
+        assert_eq!(Some(&string("a")), pattern.parse("a").unwrap().get("@key"));
+    }
+
+    #[test]
+    fn test_group_bounds() {
+        assert_eq!(
+            vec![(1, 3), (0, 10), (11, 13)],
+            group_bounds(r"((a)\(b[(])(c)")
+        );
+
+        let mut regex = r"(?i)x(?:(?<name0>\w+)=(?<name1>\d+);?){1,3}?y".to_string();
+        assert_eq!(
+            Some((
+                "map0".to_string(),
+                r"(?:(?<name0>\w+)=(?<name1>\d+);?)".to_string()
+            )),
+            wrap_repetition(&mut regex, "name0", "name1", "map0")
+        );
+        assert_eq!(
+            r"(?i)x(?<map0>(?:(?<name0>\w+)=(?<name1>\d+);?){1,3}?)y",
+            regex
+        );
+        assert_eq!("(?i)", leading_flags(&regex));
+        assert_eq!(
+            r"(?:(?:\w+)=(?:(?<=x)\d+);?)",
+            strip_group_names(r"(?:(?<name0>\w+)=(?P<name1>(?<=x)\d+);?)")
+        );
+    }
+}
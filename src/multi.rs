@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use regex::RegexSet;
+
+use crate::{Grok, GrokError, Pattern, Value};
+
+/// the index of the matching pattern, and the parse result of it.
+pub type MultiMatch = (usize, HashMap<String, Value>);
+
+/// Several patterns matched at once, e.g. to route the records of a heterogeneous log stream,
+/// see [`Grok::compile_multi`].
+#[derive(Debug)]
+pub struct MultiPattern {
+    set: RegexSet,
+    patterns: Vec<Pattern>,
+}
+
+impl MultiPattern {
+    /// parse the input string by the first pattern which matches it, in the order of the compiled list.
+    /// the index of the pattern is returned with the result, or `None` if no pattern matches.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let multi = grok.compile_multi(&["^%{IP:ip}$", "^%{WORD:word}$"], true).unwrap();
+    /// let (index, result) = multi.parse("hello").unwrap().unwrap();
+    /// assert_eq!(1, index);
+    /// assert!(result.contains_key("word"));
+    /// ```
+    pub fn parse(&self, s: &str) -> Result<Option<MultiMatch>, GrokError> {
+        match self.set.matches(s).iter().next() {
+            Some(index) => Ok(Some((index, self.patterns[index].parse(s)?))),
+            None => Ok(None),
+        }
+    }
+
+    /// the compiled patterns, in the order of the compiled list.
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+}
+
+impl Grok {
+    /// Compile the patterns into a [`MultiPattern`], which finds the first matching pattern in a single scan.
+    pub fn compile_multi(
+        &self,
+        exprs: &[&str],
+        named_capture_only: bool,
+    ) -> Result<MultiPattern, GrokError> {
+        let patterns = exprs
+            .iter()
+            .map(|expr| self.compile(expr, named_capture_only))
+            .collect::<Result<Vec<_>, _>>()?;
+        let set = RegexSet::new(patterns.iter().map(|p| p.regex.as_str()))
+            .map_err(|e| GrokError::Regex(e.to_string()))?;
+        Ok(MultiPattern { set, patterns })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_pattern() {
+        let grok = Grok::default();
+        let multi = grok
+            .compile_multi(
+                &[
+                    r"^%{IP:client} %{WORD:method} %{URIPATH:path}$",
+                    r"^%{LOGLEVEL:level}: %{GREEDYDATA:message}$",
+                    r"^%{GREEDYDATA:message}$",
+                ],
+                true,
+            )
+            .unwrap();
+        assert_eq!(3, multi.patterns().len());
+
+        assert_eq!(
+            Some((
+                1,
+                HashMap::from([
+                    ("level".to_string(), Value::String("ERROR".to_string())),
+                    (
+                        "message".to_string(),
+                        Value::String("disk full".to_string())
+                    ),
+                ])
+            )),
+            multi.parse("ERROR: disk full").unwrap()
+        );
+        assert_eq!(0, multi.parse("10.0.0.1 GET /index").unwrap().unwrap().0);
+        assert_eq!(2, multi.parse("anything else").unwrap().unwrap().0);
+
+        let multi = grok.compile_multi(&["^%{IP:ip}$"], true).unwrap();
+        assert_eq!(None, multi.parse("not an ip").unwrap());
+        assert!(matches!(
+            grok.compile_multi(&["%{NOPE}"], true),
+            Err(GrokError::PatternNotFound(_))
+        ));
+    }
+}
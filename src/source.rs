@@ -0,0 +1,394 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+
+use regex::Captures;
+
+use crate::{Grok, GrokError, DEFAULT_PATTERNS, GROK_REGEX, NAME_INDEX, PATTERN_INDEX};
+
+/// A source of pattern definitions, e.g. the embedded default patterns, a directory, or a database.
+/// The patterns are loaded into a [`Grok`](crate::Grok) by [`Grok::from_source`](crate::Grok::from_source).
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use grok_rs::{Grok, GrokError, PatternSource};
+///
+/// struct InMemory;
+///
+/// impl PatternSource for InMemory {
+///     fn patterns(&self) -> Result<HashMap<String, String>, GrokError> {
+///         Ok(HashMap::from([("NAME".to_string(), r"[a-z]+".to_string())]))
+///     }
+/// }
+///
+/// let grok = Grok::from_source(InMemory).unwrap();
+/// assert_eq!(Some("[a-z]+"), grok.pattern_definition("NAME"));
+/// ```
+pub trait PatternSource {
+    /// the pattern definitions keyed by the pattern name.
+    fn patterns(&self) -> Result<HashMap<String, String>, GrokError>;
+}
+
+/// The default patterns embedded in the crate.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultSource;
+
+impl PatternSource for DefaultSource {
+    fn patterns(&self) -> Result<HashMap<String, String>, GrokError> {
+        Ok(DEFAULT_PATTERNS.clone())
+    }
+}
+
+/// The pattern files in a directory, each line of the files is `NAME regex`,
+/// the empty lines and the lines starting with `#` are ignored.
+/// The files are loaded in the order of the file names, so the later file wins for the same pattern name.
+#[derive(Debug, Clone)]
+pub struct DirSource {
+    dir: PathBuf,
+}
+
+impl DirSource {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl PatternSource for DirSource {
+    fn patterns(&self) -> Result<HashMap<String, String>, GrokError> {
+        let io_error = |e: std::io::Error| GrokError::Io(e.to_string());
+
+        let mut files = fs::read_dir(&self.dir)
+            .map_err(io_error)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io_error)?;
+        files.retain(|path| path.is_file());
+        files.sort();
+
+        let mut patterns = HashMap::new();
+        for file in files {
+            let file = File::open(file).map_err(io_error)?;
+            patterns.extend(parse_patterns(BufReader::new(file))?);
+        }
+        Ok(patterns)
+    }
+}
+
+/// The patterns read from a reader in the same format as [`DirSource`].
+/// The reader is consumed by the first [`PatternSource::patterns`] call.
+#[derive(Debug)]
+pub struct ReaderSource<R> {
+    reader: RefCell<R>,
+}
+
+impl<R: BufRead> ReaderSource<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: RefCell::new(reader),
+        }
+    }
+}
+
+impl<R: BufRead> PatternSource for ReaderSource<R> {
+    fn patterns(&self) -> Result<HashMap<String, String>, GrokError> {
+        parse_patterns(&mut *self.reader.borrow_mut())
+    }
+}
+
+impl Grok {
+    /// add the patterns of the files in the directory, see [`DirSource`] for the format.
+    /// the existing patterns with the same name are overwritten.
+    pub fn add_patterns_from_dir<P: Into<PathBuf>>(&mut self, dir: P) -> Result<(), GrokError> {
+        self.add_patterns_from_dir_filtered(dir, |_| true)
+    }
+
+    /// add the patterns of the files in the directory like [`Grok::add_patterns_from_dir`],
+    /// but only the ones whose name is selected by the predicate, e.g. to keep the `NGINX_*` subset only.
+    /// the selected patterns may still reference the dropped ones, which fails when compiling.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use grok_rs::Grok;
+    ///
+    /// let mut grok = Grok::default();
+    /// grok.add_patterns_from_dir_filtered("patterns", |name| name.starts_with("NGINX_"))
+    ///     .unwrap();
+    /// ```
+    pub fn add_patterns_from_dir_filtered<P, F>(
+        &mut self,
+        dir: P,
+        predicate: F,
+    ) -> Result<(), GrokError>
+    where
+        P: Into<PathBuf>,
+        F: Fn(&str) -> bool,
+    {
+        for (name, pattern) in DirSource::new(dir).patterns()? {
+            if predicate(&name) {
+                self.add_pattern(name, pattern);
+            }
+        }
+        Ok(())
+    }
+
+    /// add the patterns of the files in the directory like [`Grok::add_patterns_from_dir`],
+    /// but every name is prefixed by the namespace and `::`, e.g. `HOST` of `nginx` is referenced
+    /// by `%{nginx::HOST}`, so the libraries defining the same names don't clash.
+    ///  - the references between the patterns of the library are namespaced as well,
+    ///    the other references, e.g. to the default patterns, are kept.
+    ///  - the namespace must be ASCII word characters, e.g. `nginx`, otherwise it's a `MalformedDefinition` error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use grok_rs::Grok;
+    ///
+    /// let mut grok = Grok::default();
+    /// grok.add_patterns_from_dir_namespaced("patterns/nginx", "nginx").unwrap();
+    /// let pattern = grok.compile("%{nginx::HOST:host}", true).unwrap();
+    /// ```
+    pub fn add_patterns_from_dir_namespaced<P: Into<PathBuf>>(
+        &mut self,
+        dir: P,
+        namespace: &str,
+    ) -> Result<(), GrokError> {
+        if namespace.is_empty()
+            || !namespace
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(GrokError::MalformedDefinition(namespace.to_string()));
+        }
+
+        let patterns = DirSource::new(dir).patterns()?;
+        for (name, pattern) in &patterns {
+            let pattern = GROK_REGEX.replace_all(pattern, |caps: &Captures<'_>| {
+                let referenced = &caps[PATTERN_INDEX];
+                if patterns.contains_key(referenced) {
+                    let rest = &caps[NAME_INDEX][referenced.len()..];
+                    format!("%{{{namespace}::{referenced}{rest}}}")
+                } else {
+                    caps[0].to_string()
+                }
+            });
+            self.add_pattern(format!("{namespace}::{name}"), pattern.into_owned());
+        }
+        Ok(())
+    }
+}
+
+/// parse the pattern definitions, each line is `NAME regex`.
+pub(crate) fn parse_patterns<R: BufRead>(reader: R) -> Result<HashMap<String, String>, GrokError> {
+    let mut patterns = HashMap::new();
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| GrokError::Io(e.to_string()))?;
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+
+        match line.split_once(char::is_whitespace) {
+            Some((key, value)) if !key.is_empty() => {
+                patterns.insert(key.to_string(), value.trim().to_string());
+            }
+            _ => return Err(GrokError::MalformedDefinition(line)),
+        }
+    }
+
+    Ok(patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ops::Deref, path::Path};
+
+    use super::*;
+
+    /// the temporary directory of a test, which is removed on drop, even if the test panics.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("grok-rs-{name}-{}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        /// write the file at the relative path, the parent directories are created.
+        fn write(&self, path: &str, content: &str) {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    impl Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    struct InMemory(Vec<(&'static str, &'static str)>);
+
+    impl PatternSource for InMemory {
+        fn patterns(&self) -> Result<HashMap<String, String>, GrokError> {
+            Ok(self
+                .0
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_from_source() {
+        let source = InMemory(vec![("GREETING", r"hello|hi"), ("NAME", r"[a-z]+")]);
+        let grok = Grok::from_source(source).unwrap();
+
+        let pattern = grok.compile("%{GREETING:g} %{NAME:n}", true).unwrap();
+        assert_eq!(
+            Some(&crate::Value::String("bob".into())),
+            pattern.parse("hi bob").unwrap().get("n")
+        );
+        // the default patterns are still available
+        assert!(grok.compile("%{IP}", true).is_ok());
+    }
+
+    #[test]
+    fn test_default_source() {
+        let patterns = DefaultSource.patterns().unwrap();
+        assert_eq!(
+            Some(&r"[a-zA-Z0-9._-]+".to_string()),
+            patterns.get("USERNAME")
+        );
+        assert_eq!(Some(&"true|false".to_string()), patterns.get("BOOL"));
+    }
+
+    #[test]
+    fn test_reader_source() {
+        let input = "# comment\n\nNAME [a-z]+\nGREETING\thello|hi \n";
+        let patterns = ReaderSource::new(input.as_bytes()).patterns().unwrap();
+        assert_eq!(
+            HashMap::from([
+                ("NAME".to_string(), "[a-z]+".to_string()),
+                ("GREETING".to_string(), "hello|hi".to_string()),
+            ]),
+            patterns
+        );
+
+        assert_eq!(
+            Err(GrokError::MalformedDefinition("NAME".to_string())),
+            ReaderSource::new("NAME".as_bytes()).patterns()
+        );
+    }
+
+    #[test]
+    fn test_dir_source() {
+        let dir = TempDir::new("dir-source");
+        dir.write("a", "NAME [a-z]+\nGREETING hello\n");
+        dir.write("b", "# override\nGREETING hello|hi\n");
+
+        assert_eq!(
+            HashMap::from([
+                ("NAME".to_string(), "[a-z]+".to_string()),
+                ("GREETING".to_string(), "hello|hi".to_string()),
+            ]),
+            DirSource::new(&*dir).patterns().unwrap()
+        );
+        assert!(matches!(
+            DirSource::new("/not/exist/dir").patterns(),
+            Err(GrokError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_patterns_from_dir_filtered() {
+        let dir = TempDir::new("dir-filtered");
+        dir.write(
+            "nginx",
+            "NGINX_HOST [a-z.]+\nNGINX_PORT \\d+\nSYSLOG_HOST \\S+\n",
+        );
+
+        let mut filtered = Grok::default();
+        assert_eq!(
+            Ok(()),
+            filtered.add_patterns_from_dir_filtered(&*dir, |name| name.starts_with("NGINX_"))
+        );
+        assert_eq!(Some("[a-z.]+"), filtered.pattern_definition("NGINX_HOST"));
+        assert_eq!(Some(r"\d+"), filtered.pattern_definition("NGINX_PORT"));
+        assert_eq!(None, filtered.pattern_definition("SYSLOG_HOST"));
+
+        let mut all = Grok::default();
+        assert_eq!(Ok(()), all.add_patterns_from_dir(&*dir));
+        assert_eq!(Some(r"\S+"), all.pattern_definition("SYSLOG_HOST"));
+    }
+
+    #[test]
+    fn test_add_patterns_from_dir_namespaced() {
+        let root = TempDir::new("namespaced");
+        root.write(
+            "nginx/patterns",
+            "HOST [a-z]+\nADDR %{HOST:host}:%{INT:port:int}\n",
+        );
+        root.write("apache/patterns", "HOST [0-9]+\nADDR @%{HOST:id}\n");
+
+        let mut grok = Grok::default();
+        assert_eq!(
+            Ok(()),
+            grok.add_patterns_from_dir_namespaced(root.join("nginx"), "nginx")
+        );
+        assert_eq!(
+            Ok(()),
+            grok.add_patterns_from_dir_namespaced(root.join("apache"), "apache")
+        );
+
+        assert_eq!(
+            Some("%{nginx::HOST:host}:%{INT:port:int}"),
+            grok.pattern_definition("nginx::ADDR")
+        );
+        assert_eq!(None, grok.pattern_definition("ADDR"));
+
+        let pattern = grok
+            .compile("%{nginx::ADDR} %{apache::ADDR} %{apache::HOST}", false)
+            .unwrap();
+        assert_eq!(
+            HashMap::from([
+                ("host".to_string(), crate::Value::String("web".into())),
+                ("port".to_string(), crate::Value::Int(80)),
+                ("id".to_string(), crate::Value::String("42".into())),
+                ("apache::HOST".to_string(), crate::Value::String("7".into())),
+                (
+                    "nginx::ADDR".to_string(),
+                    crate::Value::String("web:80".into())
+                ),
+                (
+                    "apache::ADDR".to_string(),
+                    crate::Value::String("@42".into())
+                ),
+            ]),
+            pattern.parse("web:80 @42 7").unwrap()
+        );
+
+        assert_eq!(
+            Err(GrokError::MalformedDefinition("a:b".to_string())),
+            grok.add_patterns_from_dir_namespaced(&*root, "a:b")
+        );
+        // the pattern names only allow the ASCII word characters
+        assert_eq!(
+            Err(GrokError::MalformedDefinition("nginxé".to_string())),
+            grok.add_patterns_from_dir_namespaced(root.join("nginx"), "nginxé")
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use regex::Regex;
+
+use crate::{Grok, DEFAULT_PATTERNS};
+
+/// the default patterns tried for each token in order, the first one which fully matches is suggested.
+const CANDIDATES: [&str; 9] = [
+    "TIMESTAMP_ISO8601",
+    "IP",
+    "NUMBER",
+    "WORD",
+    "EMAILADDRESS",
+    "URI",
+    "URIPATHPARAM",
+    "HOSTNAME",
+    "QUOTEDSTRING",
+];
+
+lazy_static::lazy_static! {
+    /// the candidates compiled from the default patterns, see [`Grok::suggest`].
+    static ref CANDIDATE_MATCHERS: Vec<(&'static str, Regex)> = candidate_matchers(&Grok::default());
+    /// the default patterns anchored at both ends, sorted by name, the ones which can't be compiled are skipped.
+    static ref BUILTIN_MATCHERS: Vec<(String, Regex)> = {
+        let grok = Grok::default();
+        let mut matchers = DEFAULT_PATTERNS
+            .keys()
+            .filter_map(|name| {
+                let pattern = grok.compile(&format!("^(?:%{{{name}}})$"), true).ok()?;
+                Some((name.clone(), pattern.regex))
+            })
+            .collect::<Vec<_>>();
+        matchers.sort_by(|a, b| a.0.cmp(&b.0));
+        matchers
+    };
+}
+
+/// the candidates anchored at both ends, in order, the ones which can't be compiled are skipped.
+fn candidate_matchers(grok: &Grok) -> Vec<(&'static str, Regex)> {
+    CANDIDATES
+        .iter()
+        .filter_map(|name| {
+            let pattern = grok.compile(&format!("^%{{{name}}}$"), true).ok()?;
+            Some((*name, pattern.regex))
+        })
+        .collect()
+}
+
+impl Grok {
+    /// Suggest the grok expressions for an unknown line, it's a minimal grok constructor.
+    ///
+    /// The line is split by whitespace, each token is replaced by the first pattern of
+    /// `TIMESTAMP_ISO8601`, `IP`, `NUMBER`, `WORD`, `EMAILADDRESS`, `URI`, `URIPATHPARAM`,
+    /// `HOSTNAME` and `QUOTEDSTRING` which matches the whole token, or `NOTSPACE` if none matches.
+    /// The tokens without any alphanumeric character, e.g. `-` or `|`, are kept as literal.
+    ///
+    /// Two candidates are returned, the specific one first, then the generic one with `NOTSPACE` only.
+    /// The suggestions are not guaranteed to be the best, review them before use.
+    /// The candidates are compiled once, unless a default pattern is redefined by this Grok.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let suggestions = grok.suggest("192.168.0.1 - GET 200");
+    /// assert_eq!("%{IP} - %{WORD} %{NUMBER}", suggestions[0]);
+    /// ```
+    pub fn suggest(&self, line: &str) -> Vec<String> {
+        let redefined;
+        let matchers = if self
+            .patterns
+            .keys()
+            .any(|name| DEFAULT_PATTERNS.contains_key(name))
+        {
+            redefined = candidate_matchers(self);
+            &redefined
+        } else {
+            &*CANDIDATE_MATCHERS
+        };
+
+        let mut specific = String::new();
+        let mut generic = String::new();
+        let mut rest = line;
+
+        while !rest.is_empty() {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let (token, tail) = rest.split_at(end);
+
+            if !token.is_empty() {
+                if token.chars().any(char::is_alphanumeric) {
+                    let name = matchers
+                        .iter()
+                        .find(|(_, regex)| regex.is_match(token))
+                        .map_or("NOTSPACE", |(name, _)| name);
+                    specific.push_str(&format!("%{{{name}}}"));
+                    generic.push_str("%{NOTSPACE}");
+                } else {
+                    let literal = escape(token);
+                    specific.push_str(&literal);
+                    generic.push_str(&literal);
+                }
+            }
+
+            let spaces = tail.len() - tail.trim_start().len();
+            let (space, tail) = tail.split_at(spaces);
+            let separator = if space == " " { " " } else { r"\s+" };
+            if !space.is_empty() {
+                specific.push_str(separator);
+                generic.push_str(separator);
+            }
+            rest = tail;
+        }
+
+        if specific.is_empty() {
+            return Vec::new();
+        }
+        if specific == generic {
+            return vec![specific];
+        }
+        vec![specific, generic]
+    }
+}
+
+impl Grok {
+    /// Return the names of the default patterns which fully match the sample, sorted by name,
+    /// e.g. to find out whether a token is an `IP`, an `IPV6` or an `IPORHOST`.
+    ///
+    /// The default patterns are compiled on the first call, the user-defined patterns are not considered.
+    ///
+    /// # Example
+    /// ```
+    /// use grok_rs::Grok;
+    ///
+    /// let grok = Grok::default();
+    /// let names = grok.matching_builtins("2001:db8::1");
+    /// assert!(names.contains(&"IPV6".to_string()));
+    /// assert!(!names.contains(&"IPV4".to_string()));
+    /// ```
+    pub fn matching_builtins(&self, sample: &str) -> Vec<String> {
+        BUILTIN_MATCHERS
+            .iter()
+            .filter(|(_, regex)| regex.is_match(sample))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+/// escape the regex meta characters only, so the literal stays readable, e.g. `-` is not escaped.
+fn escape(token: &str) -> String {
+    let mut escaped = String::with_capacity(token.len());
+    for c in token.chars() {
+        if r"\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest() {
+        let grok = Grok::default();
+        let line = "192.168.0.1 - GET 200";
+
+        let suggestions = grok.suggest(line);
+        assert_eq!(
+            vec![
+                "%{IP} - %{WORD} %{NUMBER}".to_string(),
+                "%{NOTSPACE} - %{NOTSPACE} %{NOTSPACE}".to_string(),
+            ],
+            suggestions
+        );
+        assert!(suggestions[0].contains("%{IP"));
+        assert!(suggestions[0].contains("%{NUMBER"));
+
+        // every suggestion matches the line
+        for suggestion in suggestions {
+            let pattern = grok.compile(&suggestion, false).unwrap();
+            assert_eq!(Ok(()), pattern.validate(line));
+        }
+    }
+
+    #[test]
+    fn test_suggest_more() {
+        let grok = Grok::default();
+        assert_eq!(
+            "%{TIMESTAMP_ISO8601}\\s+%{HOSTNAME} %{NOTSPACE}",
+            grok.suggest("2024-06-05T10:00:00Z   example.com [main]")[0]
+        );
+        assert_eq!(vec![" \\| ".to_string()], grok.suggest(" | "));
+        assert!(grok.suggest("").is_empty());
+
+        // the redefined default pattern is respected
+        let grok = Grok::from([("NUMBER", "x")]);
+        assert_eq!("%{WORD} %{WORD}", grok.suggest("GET 200")[0]);
+    }
+
+    #[test]
+    fn test_matching_builtins() {
+        let grok = Grok::default();
+        let names = grok.matching_builtins("192.168.1.1");
+        for name in ["IPV4", "IP", "IPORHOST", "HOSTNAME"] {
+            assert!(names.contains(&name.to_string()), "{name}");
+        }
+        assert!(!names.contains(&"IPV6".to_string()));
+        assert!(!names.contains(&"INT".to_string()));
+
+        let names = grok.matching_builtins("GET");
+        assert!(names.contains(&"WORD".to_string()));
+        assert!(names.contains(&"HTTPDUSER".to_string()));
+        assert!(!names.contains(&"INT".to_string()));
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(sorted, names);
+    }
+}